@@ -0,0 +1,186 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+/// How `Keyframes` get from one key to the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Straight lines with constant speed between the keys.
+    #[default]
+    Linear,
+    /// Straight lines that ease in and out of every key.
+    Smoothstep,
+    /// A curve through all keys whose tangent at a key points from the
+    /// previous key to the next one, for smooth paths.
+    CatmullRom,
+}
+
+impl FromStr for Interpolation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Interpolation::Linear),
+            "smoothstep" => Ok(Interpolation::Smoothstep),
+            "catmull_rom" => Ok(Interpolation::CatmullRom),
+            _ => Err(format!("unknown interpolation `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Interpolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Interpolation::Linear => write!(f, "linear"),
+            Interpolation::Smoothstep => write!(f, "smoothstep"),
+            Interpolation::CatmullRom => write!(f, "catmull_rom"),
+        }
+    }
+}
+
+/// A value that changes over time, given at a few points in time and
+/// interpolated in between, linearly unless set otherwise. Before the first
+/// and after the last key the value is held.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyframes<T> {
+    keys: Vec<(f32, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T> Keyframes<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    /// Panics if `keys` is empty or its times do not strictly increase.
+    pub fn new(keys: Vec<(f32, T)>) -> Self {
+        assert!(!keys.is_empty(), "keyframes need at least one key");
+        assert!(
+            keys.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "keyframe times must increase"
+        );
+        Self {
+            keys,
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    /// A value that does not change.
+    pub fn constant(value: T) -> Self {
+        Self::new(vec![(0.0, value)])
+    }
+
+    pub fn keys(&self) -> &[(f32, T)] {
+        &self.keys
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    pub fn at(&self, time: f32) -> T {
+        let next = self.keys.partition_point(|&(t, _)| t <= time);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1;
+        }
+        let (t0, a) = self.keys[next - 1];
+        let (t1, b) = self.keys[next];
+        let u = (time - t0) / (t1 - t0);
+        match self.interpolation {
+            Interpolation::Linear => a + (b - a) * u,
+            Interpolation::Smoothstep => a + (b - a) * (u * u * (3.0 - 2.0 * u)),
+            Interpolation::CatmullRom => {
+                // Cubic Hermite curve, the tangents are scaled from rates per
+                // unit of time to the span between the two keys.
+                let span = t1 - t0;
+                let m0 = self.tangent(next - 1) * span;
+                let m1 = self.tangent(next) * span;
+                let (u2, u3) = (u * u, u * u * u);
+                a * (2.0 * u3 - 3.0 * u2 + 1.0)
+                    + m0 * (u3 - 2.0 * u2 + u)
+                    + b * (3.0 * u2 - 2.0 * u3)
+                    + m1 * (u3 - u2)
+            }
+        }
+    }
+
+    /// Points whose convex hull contains every value the keyframes take, for
+    /// bounding a path. Besides the keys these are the inner Bézier control
+    /// points of Catmull-Rom segments, which can overshoot the keys.
+    pub fn hull(&self) -> Vec<T> {
+        let mut points: Vec<T> = self.keys.iter().map(|&(_, value)| value).collect();
+        if self.interpolation == Interpolation::CatmullRom {
+            for i in 1..self.keys.len() {
+                let (t0, a) = self.keys[i - 1];
+                let (t1, b) = self.keys[i];
+                let span = t1 - t0;
+                points.push(a + self.tangent(i - 1) * (span / 3.0));
+                points.push(b - self.tangent(i) * (span / 3.0));
+            }
+        }
+        points
+    }
+
+    // Rate of change at key `i` for Catmull-Rom curves, from its neighbours,
+    // or one-sided at the first and last key.
+    fn tangent(&self, i: usize) -> T {
+        let (t0, a) = self.keys[i.saturating_sub(1)];
+        let (t1, b) = self.keys[(i + 1).min(self.keys.len() - 1)];
+        (b - a) * (1.0 / (t1 - t0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{Interpolation, Keyframes};
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_keyframes() {
+        let path = Keyframes::new(vec![
+            (0.0, Vec3::default()),
+            (1.0, Vec3::new(2.0, 0.0, 0.0)),
+            (3.0, Vec3::new(2.0, 4.0, 0.0)),
+        ]);
+        assert_eq!(Vec3::default(), path.at(-1.0));
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), path.at(0.5));
+        assert_eq!(Vec3::new(2.0, 0.0, 0.0), path.at(1.0));
+        assert_eq!(Vec3::new(2.0, 1.0, 0.0), path.at(1.5));
+        assert_eq!(Vec3::new(2.0, 4.0, 0.0), path.at(10.0));
+        assert_eq!(0.5, Keyframes::constant(0.5).at(3.0));
+    }
+
+    #[test]
+    fn test_keyframes_interpolation() {
+        let mut keys = Keyframes::new(vec![(0.0, 0.0), (1.0, 1.0), (2.0, 4.0), (4.0, 4.0)]);
+        for interpolation in [
+            Interpolation::Linear,
+            Interpolation::Smoothstep,
+            Interpolation::CatmullRom,
+        ] {
+            keys.set_interpolation(interpolation);
+            assert_eq!(Ok(interpolation), interpolation.to_string().parse());
+            for &(time, value) in keys.keys() {
+                assert!((keys.at(time) - value).abs() < 1e-6);
+            }
+        }
+
+        keys.set_interpolation(Interpolation::Smoothstep);
+        assert_eq!(0.5, keys.at(0.5));
+        assert!((keys.at(0.25) - 0.15625).abs() < 1e-6);
+        assert!((keys.at(3.9) - 4.0).abs() < 1e-6);
+
+        // The curve passes the middle key with the slope from its neighbours,
+        // (4 - 0) / (2 - 0) = 2 per unit of time.
+        keys.set_interpolation(Interpolation::CatmullRom);
+        let slope = (keys.at(1.001) - keys.at(0.999)) / 0.002;
+        assert!((slope - 2.0).abs() < 1e-2, "{}", slope);
+        assert!("cubic".parse::<Interpolation>().is_err());
+    }
+}
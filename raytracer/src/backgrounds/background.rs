@@ -0,0 +1,8 @@
+use crate::vec3::Vec3;
+
+/// What rays see when they leave the scene without hitting anything, as if
+/// it were infinitely far away.
+pub trait Background {
+    /// Returns the radiance arriving from the unit direction `dir`.
+    fn radiance(&self, dir: Vec3) -> Vec3;
+}
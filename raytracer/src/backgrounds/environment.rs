@@ -0,0 +1,95 @@
+use crate::backgrounds::background::Background;
+use crate::textures::image::ImageTexture;
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// An environment map in equirectangular projection: longitude runs along
+/// the width, starting and ending behind the default camera and passing
+/// -z in the middle, latitude along the height with straight up at the top.
+#[derive(Clone)]
+pub struct EnvironmentMap {
+    image: Arc<ImageTexture>,
+    rotation: f32,
+    intensity: f32,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Arc<ImageTexture>) -> Self {
+        Self {
+            image,
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+
+    /// Angle in radians by which the map is turned about the y axis.
+    pub fn rotation(&self) -> f32 {
+        self.rotation
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    pub fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let longitude = dir.x().atan2(-dir.z()) - self.rotation;
+        let u = 0.5 + longitude / (2.0 * PI);
+        let v = 0.5 + dir.y().clamp(-1.0, 1.0).asin() / PI;
+        self.image.value(u, v, dir) * self.intensity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backgrounds::background::Background;
+    use crate::backgrounds::environment::EnvironmentMap;
+    use crate::textures::image::ImageTexture;
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_environment_map() {
+        // Four columns, each a quarter turn wide, above a black row. The
+        // diagonals hit the pixel centers 45 degrees above the horizon.
+        let colors = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ];
+        let mut pixels = colors.to_vec();
+        pixels.extend([Vec3::default(); 4]);
+        let mut map = EnvironmentMap::new(Arc::new(ImageTexture::new(4, 2, pixels)));
+
+        let up = 2.0f32.sqrt();
+        let directions = [
+            Vec3::new(-1.0, up, 1.0),
+            Vec3::new(-1.0, up, -1.0),
+            Vec3::new(1.0, up, -1.0),
+            Vec3::new(1.0, up, 1.0),
+        ];
+        for (dir, color) in directions.iter().zip(colors) {
+            assert!((map.radiance(dir.norm()) - color).length() < 1e-4);
+        }
+        let below = Vec3::new(1.0, -up, -1.0).norm();
+        assert!(map.radiance(below).length() < 1e-4);
+
+        map.set_rotation(PI / 2.0);
+        map.set_intensity(2.0);
+        let turned = map.radiance(directions[3].norm());
+        assert!((turned - colors[2] * 2.0).length() < 1e-4);
+    }
+}
@@ -0,0 +1,46 @@
+use crate::backgrounds::background::Background;
+use crate::vec3::Vec3;
+
+/// Blends from `bottom` straight down to `top` straight up.
+#[derive(Clone)]
+pub struct Gradient {
+    bottom: Vec3,
+    top: Vec3,
+}
+
+impl Gradient {
+    pub fn new(bottom: Vec3, top: Vec3) -> Self {
+        Self { bottom, top }
+    }
+}
+
+impl Background for Gradient {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        let t = 0.5 * (dir.y() + 1.0);
+        self.bottom * (1.0 - t) + self.top * t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backgrounds::background::Background;
+    use crate::backgrounds::gradient::Gradient;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_gradient() {
+        let gradient = Gradient::new(Vec3::default(), Vec3::new(1.0, 1.0, 1.0));
+        assert_eq!(
+            Vec3::default(),
+            gradient.radiance(Vec3::new(0.0, -1.0, 0.0))
+        );
+        assert_eq!(
+            Vec3::new(0.5, 0.5, 0.5),
+            gradient.radiance(Vec3::new(1.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            Vec3::new(1.0, 1.0, 1.0),
+            gradient.radiance(Vec3::new(0.0, 1.0, 0.0))
+        );
+    }
+}
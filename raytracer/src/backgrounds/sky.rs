@@ -0,0 +1,183 @@
+use crate::backgrounds::background::Background;
+use crate::vec3::Vec3;
+
+// Sine of the lowest elevation the sky is evaluated at.
+const MIN_ELEVATION: f32 = 0.01;
+
+// Coefficients A to E of the Perez distribution for one of Y, x and y.
+type Perez = [f32; 5];
+
+/// Analytic daylight sky (Preetham, Shirley and Smits 1999) for a sun in
+/// direction `sun`, on an atmosphere whose haze is given by `turbidity`:
+/// 2 is a very clear sky, 3 clear, 6 and up hazy. The radiance is scaled so
+/// that the sky straight up has a luminance of `intensity`.
+///
+/// The model holds for a sun above the horizon, lower suns are taken to
+/// stand on the horizon. Directions below the horizon see the sky just above
+/// it, as if looking at a bright haze.
+#[derive(Debug, Clone)]
+pub struct Sky {
+    sun: Vec3,
+    turbidity: f32,
+    intensity: f32,
+    theta_sun: f32,
+    perez: [Perez; 3],
+    zenith: [f32; 2],
+}
+
+impl Sky {
+    pub fn new(sun: Vec3, turbidity: f32, intensity: f32) -> Self {
+        let sun = sun.norm();
+        let t = turbidity;
+        let theta_sun = sun.y().clamp(0.0, 1.0).acos();
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+        // Chromaticity of the zenith, as fitted by Preetham et al.
+        let cubic = |c: [f32; 4]| {
+            c[0] * theta_sun.powi(3) + c[1] * theta_sun.powi(2) + c[2] * theta_sun + c[3]
+        };
+        let zenith = [
+            t * t * cubic([0.00166, -0.00375, 0.00209, 0.0])
+                + t * cubic([-0.02903, 0.06377, -0.03202, 0.00394])
+                + cubic([0.11693, -0.21196, 0.06052, 0.25886]),
+            t * t * cubic([0.00275, -0.00610, 0.00317, 0.0])
+                + t * cubic([-0.04214, 0.08970, -0.04153, 0.00516])
+                + cubic([0.15346, -0.26756, 0.06670, 0.26688]),
+        ];
+        Self {
+            sun,
+            turbidity,
+            intensity,
+            theta_sun,
+            perez,
+            zenith,
+        }
+    }
+
+    /// Unit direction towards the sun.
+    pub fn sun(&self) -> Vec3 {
+        self.sun
+    }
+
+    pub fn turbidity(&self) -> f32 {
+        self.turbidity
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Color of the sky around the sun with a luminance of 1, to light the
+    /// scene with a matching sun.
+    pub fn sun_color(&self) -> Vec3 {
+        let (x, y) = self.chromaticity(self.sun.y().max(0.0).acos(), 0.0);
+        xyy_to_rgb(x, y, 1.0)
+    }
+
+    // Perez et al.'s luminance distribution, relative to the zenith, for the
+    // angles from the zenith and from the sun.
+    fn relative(&self, perez: &Perez, theta: f32, gamma: f32) -> f32 {
+        let f = |theta: f32, gamma: f32| {
+            let [a, b, c, d, e] = *perez;
+            (1.0 + a * (b / theta.cos()).exp())
+                * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+        };
+        f(theta, gamma) / f(0.0, self.theta_sun)
+    }
+
+    fn chromaticity(&self, theta: f32, gamma: f32) -> (f32, f32) {
+        (
+            self.zenith[0] * self.relative(&self.perez[1], theta, gamma),
+            self.zenith[1] * self.relative(&self.perez[2], theta, gamma),
+        )
+    }
+}
+
+impl Background for Sky {
+    fn radiance(&self, dir: Vec3) -> Vec3 {
+        // Keep the angle from the zenith off 90 degrees, where the model
+        // divides by zero, by lifting lower directions to just above the
+        // horizon.
+        let dir = if dir.y() < MIN_ELEVATION {
+            let horizontal = Vec3::new(dir.x(), 0.0, dir.z());
+            let horizontal = if horizontal.length() > 0.0 {
+                horizontal.norm()
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+            horizontal * (1.0 - MIN_ELEVATION * MIN_ELEVATION).sqrt()
+                + Vec3::new(0.0, MIN_ELEVATION, 0.0)
+        } else {
+            dir
+        };
+        let theta = dir.y().acos();
+        let gamma = (dir * self.sun).clamp(-1.0, 1.0).acos();
+        let luminance = self.intensity * self.relative(&self.perez[0], theta, gamma);
+        let (x, y) = self.chromaticity(theta, gamma);
+        xyy_to_rgb(x, y, luminance)
+    }
+}
+
+// CIE xyY to linear sRGB, colors outside of its gamut are clipped.
+fn xyy_to_rgb(x: f32, y: f32, luminance: f32) -> Vec3 {
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+    Vec3::new(
+        (3.2406 * big_x - 1.5372 * luminance - 0.4986 * big_z).max(0.0),
+        (-0.9689 * big_x + 1.8758 * luminance + 0.0415 * big_z).max(0.0),
+        (0.0557 * big_x - 0.2040 * luminance + 1.0570 * big_z).max(0.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backgrounds::background::Background;
+    use crate::backgrounds::sky::Sky;
+    use crate::vec3::Vec3;
+
+    fn luminance(color: Vec3) -> f32 {
+        0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+    }
+
+    #[test]
+    fn test_sky() {
+        let sky = Sky::new(Vec3::new(1.0, 1.0, 0.0), 3.0, 2.0);
+        let zenith = sky.radiance(Vec3::new(0.0, 1.0, 0.0));
+        assert!((luminance(zenith) - 2.0).abs() < 0.05, "{:?}", zenith);
+        // Blue overhead, brighter towards the sun than away from it.
+        assert!(zenith.z() > zenith.x());
+        let towards = sky.radiance(Vec3::new(1.0, 0.2, 0.0).norm());
+        let away = sky.radiance(Vec3::new(-1.0, 0.2, 0.0).norm());
+        assert!(luminance(towards) > luminance(away));
+        // Below the horizon the sky continues the horizon.
+        let below = sky.radiance(Vec3::new(-1.0, -0.5, 0.0).norm());
+        assert!((below - sky.radiance(Vec3::new(-1.0, 0.0, 0.0))).length() < 1e-6);
+
+        let sun = sky.sun_color();
+        assert!((luminance(sun) - 1.0).abs() < 0.05, "{:?}", sun);
+        // A low sun shines through more air and turns red.
+        let sunset = Sky::new(Vec3::new(1.0, 0.1, 0.0), 3.0, 1.0).sun_color();
+        assert!(sunset.x() > sunset.z() && sun.x() < sun.z());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::backgrounds::background::Background;
+use crate::vec3::Vec3;
+
+#[derive(Clone)]
+pub struct SolidBackground {
+    color: Vec3,
+}
+
+impl SolidBackground {
+    pub fn new(color: Vec3) -> Self {
+        Self { color }
+    }
+
+    pub fn color(&self) -> Vec3 {
+        self.color
+    }
+}
+
+impl Background for SolidBackground {
+    fn radiance(&self, _dir: Vec3) -> Vec3 {
+        self.color
+    }
+}
@@ -0,0 +1,253 @@
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    position: Vec3,
+    look_at: Vec3,
+    up: Vec3,
+    fov: f32,
+    aspect: f32,
+    aperture: f32,
+    focus_distance: Option<f32>,
+    shutter: (f32, f32),
+    // Orthonormal basis: `u` points right, `v` up and `w` backwards.
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    half_height: f32,
+}
+
+impl Camera {
+    /// `fov` is the vertical field of view in radians and `aspect` the ratio
+    /// of image width to height. `up` must not be parallel to the viewing
+    /// direction.
+    pub fn new(position: Vec3, look_at: Vec3, up: Vec3, fov: f32, aspect: f32) -> Self {
+        let mut camera = Self {
+            position,
+            look_at,
+            up,
+            fov,
+            aspect,
+            aperture: 0.0,
+            focus_distance: None,
+            shutter: (0.0, 0.0),
+            u: Vec3::default(),
+            v: Vec3::default(),
+            w: Vec3::default(),
+            half_height: 0.0,
+        };
+        camera.update();
+        camera
+    }
+
+    fn update(&mut self) {
+        self.w = (self.position - self.look_at).norm();
+        self.u = self.up.cross(self.w).norm();
+        self.v = self.w.cross(self.u);
+        self.half_height = (self.fov / 2.0).tan();
+    }
+
+    /// Returns the ray through the point `(s, t)` of the image, where
+    /// `(0, 0)` is the top left and `(1, 1)` the bottom right corner, sent at
+    /// `time`. `lens` is a point on the unit disk that picks where the ray
+    /// leaves the lens, all rays through one image point meet again at the
+    /// focus distance. Without aperture every ray starts at the camera
+    /// position.
+    pub fn ray(&self, s: f32, t: f32, lens: (f32, f32), time: f32) -> Ray {
+        let x = (2.0 * s - 1.0) * self.half_height * self.aspect;
+        let y = (1.0 - 2.0 * t) * self.half_height;
+        let dir = self.u * x + self.v * y - self.w;
+        if self.aperture == 0.0 {
+            return Ray::at_time(self.position, dir.norm(), time);
+        }
+        let focus = self.position + dir * self.focus_distance();
+        let orig = self.position + (self.u * lens.0 + self.v * lens.1) * self.aperture;
+        Ray::at_time(orig, (focus - orig).norm(), time)
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn look_at(&self) -> Vec3 {
+        self.look_at
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.up
+    }
+
+    pub fn fov(&self) -> f32 {
+        self.fov
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    /// Radius of the lens, 0 for a pinhole camera that keeps everything in
+    /// focus.
+    pub fn aperture(&self) -> f32 {
+        self.aperture
+    }
+
+    /// Distance of the plane in perfect focus, measured along the viewing
+    /// direction. Follows `look_at` unless it is set explicitly.
+    pub fn focus_distance(&self) -> f32 {
+        self.focus_distance
+            .unwrap_or_else(|| (self.look_at - self.position).length())
+    }
+
+    /// Time at which the shutter opens, rays are spread evenly between this
+    /// and `shutter_close`.
+    pub fn shutter_open(&self) -> f32 {
+        self.shutter.0
+    }
+
+    pub fn shutter_close(&self) -> f32 {
+        self.shutter.1
+    }
+
+    pub fn set_position(&mut self, position: Vec3) {
+        self.position = position;
+        self.update();
+    }
+
+    pub fn set_look_at(&mut self, look_at: Vec3) {
+        self.look_at = look_at;
+        self.update();
+    }
+
+    pub fn set_up(&mut self, up: Vec3) {
+        self.up = up;
+        self.update();
+    }
+
+    pub fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.update();
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+
+    pub fn set_aperture(&mut self, aperture: f32) {
+        self.aperture = aperture;
+    }
+
+    pub fn set_focus_distance(&mut self, focus_distance: f32) {
+        self.focus_distance = Some(focus_distance);
+    }
+
+    /// Panics if the shutter closes before it opens.
+    pub fn set_shutter(&mut self, open: f32, close: f32) {
+        assert!(open <= close, "shutter must not close before it opens");
+        self.shutter = (open, close);
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new(
+            Vec3::default(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            std::f32::consts::PI / 3.0,
+            16.0 / 9.0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::camera::Camera;
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+
+    fn assert_close(expected: Vec3, actual: Vec3) {
+        assert!(
+            (expected - actual).length() < 1e-6,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_camera_center_ray() {
+        let camera = Camera::new(
+            Vec3::new(1.0, 2.0, 3.0),
+            Vec3::new(1.0, 2.0, 13.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            PI / 2.0,
+            2.0,
+        );
+        let ray = camera.ray(0.5, 0.5, (0.0, 0.0), 0.25);
+        assert_eq!(Vec3::new(1.0, 2.0, 3.0), ray.orig());
+        assert_close(Vec3::new(0.0, 0.0, 1.0), ray.dir());
+        assert_eq!(0.25, ray.time());
+    }
+
+    #[test]
+    fn test_camera_corners() {
+        let camera = Camera::new(
+            Vec3::default(),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            PI / 2.0,
+            2.0,
+        );
+        assert_close(
+            Vec3::new(-2.0, 1.0, -1.0).norm(),
+            camera.ray(0.0, 0.0, (0.0, 0.0), 0.0).dir(),
+        );
+        assert_close(
+            Vec3::new(2.0, -1.0, -1.0).norm(),
+            camera.ray(1.0, 1.0, (0.0, 0.0), 0.0).dir(),
+        );
+    }
+
+    #[test]
+    fn test_camera_up() {
+        let camera = Camera::new(
+            Vec3::default(),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            PI / 2.0,
+            1.0,
+        );
+        assert_close(
+            Vec3::new(0.0, -1.0, -1.0).norm(),
+            camera.ray(0.5, 0.0, (0.0, 0.0), 0.0).dir(),
+        );
+    }
+
+    #[test]
+    fn test_camera_thin_lens() {
+        let mut camera = Camera::new(
+            Vec3::default(),
+            Vec3::new(0.0, 0.0, -4.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            PI / 2.0,
+            1.0,
+        );
+        assert_eq!(4.0, camera.focus_distance());
+        camera.set_aperture(0.5);
+        // Rays through the same image point meet on the focus plane.
+        let focus = Vec3::new(-4.0, 4.0, -4.0);
+        for lens in [(0.0, 0.0), (1.0, 0.0), (-0.6, 0.8)] {
+            let ray = camera.ray(0.0, 0.0, lens, 0.0);
+            assert_close(Vec3::new(lens.0 * 0.5, lens.1 * 0.5, 0.0), ray.orig());
+            assert_close((focus - ray.orig()).norm(), ray.dir());
+        }
+
+        camera.set_focus_distance(2.0);
+        camera.set_look_at(Vec3::new(0.0, 0.0, -10.0));
+        assert_eq!(2.0, camera.focus_distance());
+        let ray = camera.ray(0.5, 0.5, (0.0, 1.0), 0.0);
+        assert_close(Vec3::new(0.0, -0.5, -2.0).norm(), ray.dir());
+        assert_eq!(Vec3::new(0.0, 0.5, 0.0), ray.orig());
+    }
+}
@@ -0,0 +1,323 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use raytracer::film::aov::Aov;
+use raytracer::film::output::OutputFormat;
+use raytracer::film::tonemap::ToneMapping;
+use raytracer::raytracing::render::Integrator;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(version, about = "Multithreaded raytracer")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Render a scene description file into an image
+    Render(RenderArgs),
+}
+
+#[derive(Args)]
+pub struct RenderArgs {
+    /// Scene description file
+    pub scene: PathBuf,
+
+    /// Output image, the format is picked from the extension. .exr, .hdr and
+    /// .pfm keep linear HDR values, other formats are tone mapped to sRGB
+    #[arg(short, long, default_value = "image.png", value_parser = output_path)]
+    pub output: PathBuf,
+
+    /// Image width in pixels [default: taken from the scene]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+
+    /// Image height in pixels [default: taken from the scene]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: Option<u32>,
+
+    /// Vertical field of view in degrees [default: taken from the scene]
+    #[arg(long, value_parser = fov)]
+    pub fov: Option<f32>,
+
+    /// Lens radius for depth of field, 0 keeps everything in focus [default: taken from the scene]
+    #[arg(long, allow_negative_numbers = true, value_parser = aperture)]
+    pub aperture: Option<f32>,
+
+    /// Distance of the sharpest plane from the camera [default: taken from the scene]
+    #[arg(long, value_parser = focus_distance)]
+    pub focus_distance: Option<f32>,
+
+    /// Rays per pixel, jittered over a stratified grid [default: taken from the scene]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: Option<u32>,
+
+    /// Passes the samples are spread over, the output is updated after every
+    /// pass to preview the render
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub passes: u32,
+
+    /// Edge length in pixels of the tiles rendered in parallel
+    #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u32).range(1..))]
+    pub tile_size: u32,
+
+    /// Render the frames N..M of an animation (both included) into numbered
+    /// images, e.g. frame_0001.png for -o frame.png
+    #[arg(long, value_name = "N..M", value_parser = frames)]
+    pub frames: Option<(u32, u32)>,
+
+    /// Seed for the sample jitter, equal seeds give identical images [default: taken from the scene]
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Light transport algorithm [default: taken from the scene]
+    #[arg(long, value_enum)]
+    pub integrator: Option<IntegratorArg>,
+
+    /// Number of worker threads [default: one per logical CPU]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Maximum number of reflection and refraction bounces of Whitted-style
+    /// tracing [default: 4]
+    #[arg(long, value_parser = clap::value_parser!(u32).range(0..=64))]
+    pub max_depth: Option<u32>,
+
+    /// Operator that maps HDR colors into the range of 8 bit images
+    #[arg(long, value_enum, default_value_t = ToneMappingArg::Clamp)]
+    pub tonemap: ToneMappingArg,
+
+    /// Exposure adjustment in stops, every stop doubles the brightness
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true, value_parser = exposure)]
+    pub exposure: f32,
+
+    /// Smooth the noise of the render with an edge-avoiding filter guided by
+    /// the albedo and normals
+    #[arg(long)]
+    pub denoise: bool,
+
+    /// Auxiliary images of the first hits, each saved next to the output as
+    /// e.g. image.depth.png
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub aov: Vec<AovArg>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum IntegratorArg {
+    /// Whitted-style ray tracing with point lights, mirrors and refraction
+    Whitted,
+    /// Monte Carlo path tracing with diffuse interreflection and emission
+    Path,
+}
+
+impl From<IntegratorArg> for Integrator {
+    fn from(arg: IntegratorArg) -> Self {
+        match arg {
+            IntegratorArg::Whitted => Integrator::Whitted,
+            IntegratorArg::Path => Integrator::Path,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum AovArg {
+    /// Distance along the viewing direction
+    Depth,
+    /// World space shading normal
+    Normal,
+    /// Diffuse surface color
+    Albedo,
+    /// Index of the object in the scene, from 1
+    ObjectId,
+    /// Named materials counted from 1 in order of declaration
+    MaterialId,
+    /// World space position
+    Position,
+}
+
+impl From<AovArg> for Aov {
+    fn from(arg: AovArg) -> Self {
+        match arg {
+            AovArg::Depth => Aov::Depth,
+            AovArg::Normal => Aov::Normal,
+            AovArg::Albedo => Aov::Albedo,
+            AovArg::ObjectId => Aov::ObjectId,
+            AovArg::MaterialId => Aov::MaterialId,
+            AovArg::Position => Aov::Position,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ToneMappingArg {
+    /// Cut off every channel at the maximum
+    Clamp,
+    /// Reinhard's c / (1 + c), compresses highlights
+    Reinhard,
+    /// Filmic curve of the Academy Color Encoding System
+    Aces,
+}
+
+impl From<ToneMappingArg> for ToneMapping {
+    fn from(arg: ToneMappingArg) -> Self {
+        match arg {
+            ToneMappingArg::Clamp => ToneMapping::Clamp,
+            ToneMappingArg::Reinhard => ToneMapping::Reinhard,
+            ToneMappingArg::Aces => ToneMapping::Aces,
+        }
+    }
+}
+
+fn output_path(s: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(s);
+    match OutputFormat::from_path(&path) {
+        Some(_) => Ok(path),
+        None => {
+            Err("unsupported image format, use e.g. .png, .jpg, .exr, .hdr or .pfm".to_string())
+        }
+    }
+}
+
+fn exposure(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(exposure) if exposure.is_finite() => Ok(exposure),
+        _ => Err("expected a number of stops".to_string()),
+    }
+}
+
+fn fov(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(fov) if fov > 0.0 && fov < 180.0 => Ok(fov),
+        _ => Err("expected an angle between 0 and 180 degrees".to_string()),
+    }
+}
+
+fn aperture(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(aperture) if aperture.is_finite() && aperture >= 0.0 => Ok(aperture),
+        _ => Err("expected a lens radius of at least 0".to_string()),
+    }
+}
+
+fn focus_distance(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(distance) if distance.is_finite() && distance > 0.0 => Ok(distance),
+        _ => Err("expected a positive distance".to_string()),
+    }
+}
+
+fn frames(s: &str) -> Result<(u32, u32), String> {
+    let range = match s.split_once("..") {
+        Some((first, last)) => first.parse().ok().zip(last.parse().ok()),
+        None => s.parse().ok().map(|frame| (frame, frame)),
+    };
+    match range {
+        Some((first, last)) if first <= last => Ok((first, last)),
+        _ => Err("expected a frame or a range of frames like 1..48".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Cli, Command};
+    use clap::{error::ErrorKind, CommandFactory, Parser};
+    use raytracer::film::aov::Aov;
+    use raytracer::film::tonemap::ToneMapping;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_cli_render() {
+        let cli = Cli::try_parse_from([
+            "raytracer",
+            "render",
+            "scene.txt",
+            "-o",
+            "out.jpg",
+            "--width",
+            "640",
+            "--fov",
+            "90",
+            "--aperture",
+            "0.25",
+            "--samples",
+            "16",
+            "--passes",
+            "4",
+            "--frames",
+            "1..48",
+            "--tonemap",
+            "aces",
+            "--exposure",
+            "-1.5",
+            "--denoise",
+            "--aov",
+            "depth,object-id",
+        ])
+        .unwrap();
+
+        let Command::Render(args) = cli.command;
+        assert_eq!("scene.txt", args.scene.to_str().unwrap());
+        assert_eq!("out.jpg", args.output.to_str().unwrap());
+        assert_eq!(Some(640), args.width);
+        assert_eq!(None, args.height);
+        assert_eq!(Some(90.0), args.fov);
+        assert_eq!(Some(0.25), args.aperture);
+        assert_eq!(None, args.focus_distance);
+        assert_eq!(Some(16), args.samples);
+        assert_eq!(4, args.passes);
+        assert_eq!(Some((1, 48)), args.frames);
+        assert_eq!(32, args.tile_size);
+        assert_eq!(None, args.seed);
+        assert_eq!(ToneMapping::Aces, args.tonemap.into());
+        assert_eq!(-1.5, args.exposure);
+        assert!(args.denoise);
+        let aovs: Vec<Aov> = args.aov.into_iter().map(Aov::from).collect();
+        assert_eq!(vec![Aov::Depth, Aov::ObjectId], aovs);
+
+        let cli =
+            Cli::try_parse_from(["raytracer", "render", "scene.txt", "-o", "out.pfm"]).unwrap();
+        let Command::Render(args) = cli.command;
+        assert_eq!(ToneMapping::Clamp, args.tonemap.into());
+        assert_eq!(0.0, args.exposure);
+        assert!(!args.denoise);
+        assert_eq!(None, args.frames);
+        assert!(args.aov.is_empty());
+    }
+
+    #[test]
+    fn test_cli_invalid() {
+        let invalid = [
+            ["--width", "0"],
+            ["--fov", "180"],
+            ["--aperture", "-0.1"],
+            ["--focus-distance", "0"],
+            ["--threads", "0"],
+            ["--samples", "0"],
+            ["--passes", "0"],
+            ["--tile-size", "0"],
+            ["--frames", "5..2"],
+            ["--frames", "1..x"],
+            ["-o", "out.txt"],
+            ["--exposure", "inf"],
+        ];
+        for [flag, value] in invalid {
+            let err = Cli::try_parse_from(["raytracer", "render", "scene.txt", flag, value])
+                .err()
+                .unwrap();
+            assert_eq!(ErrorKind::ValueValidation, err.kind());
+        }
+    }
+
+    #[test]
+    fn test_cli_unknown_integrator() {
+        let err =
+            Cli::try_parse_from(["raytracer", "render", "scene.txt", "--integrator", "photon"])
+                .err()
+                .unwrap();
+        assert_eq!(ErrorKind::InvalidValue, err.kind());
+    }
+}
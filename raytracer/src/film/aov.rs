@@ -0,0 +1,230 @@
+use crate::camera::Camera;
+use crate::film::framebuffer::Framebuffer;
+use crate::film::output::{self, OutputError, OutputFormat};
+use crate::film::tonemap::ToneMapping;
+use crate::objects::object::Hit;
+use crate::vec3::Vec3;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Arbitrary output variable, an auxiliary image of what the camera rays hit
+/// first. Pixels where nothing is hit are 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    /// Distance of the hit along the viewing direction of the camera.
+    Depth,
+    /// World space shading normal, pointing out of the object.
+    Normal,
+    /// Diffuse surface color, textures included.
+    Albedo,
+    /// Index of the object in the scene, counting from 1.
+    ObjectId,
+    /// ID of the material, see `Material::id`.
+    MaterialId,
+    /// World space position.
+    Position,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 6] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Position,
+    ];
+
+    /// Whether the values of the samples of a pixel are averaged. IDs are
+    /// taken from a single sample instead, blending them would make up IDs
+    /// at the edges.
+    pub fn is_filtered(self) -> bool {
+        !matches!(self, Aov::ObjectId | Aov::MaterialId)
+    }
+
+    /// Value at a hit on the object with index `object` of the scene.
+    pub fn value(self, hit: &Hit, object: usize, camera: &Camera) -> Vec3 {
+        let grey = |value: f32| Vec3::new(value, value, value);
+        match self {
+            Aov::Depth => {
+                let forward = (camera.look_at() - camera.position()).norm();
+                grey((hit.point - camera.position()) * forward)
+            }
+            Aov::Normal => hit.shading_normal,
+            Aov::Albedo => hit.material.diffuse_color_at(hit.uv.0, hit.uv.1, hit.point),
+            Aov::ObjectId => grey((object + 1) as f32),
+            Aov::MaterialId => grey(hit.material.id() as f32),
+            Aov::Position => hit.point,
+        }
+    }
+
+    /// Maps the values into the range of 8 bit images: normals and positions
+    /// to colors, depths to grey levels and IDs to distinct colors.
+    pub fn visualize(self, framebuffer: &Framebuffer) -> Framebuffer {
+        let mut image = framebuffer.clone();
+        let max = framebuffer
+            .pixels()
+            .iter()
+            .map(|p| p.x().abs().max(p.y().abs()).max(p.z().abs()))
+            .fold(0.0, f32::max)
+            .max(f32::MIN_POSITIVE);
+        for pixel in image.pixels_mut() {
+            *pixel = match self {
+                Aov::Depth => *pixel * (1.0 / max),
+                Aov::Normal => *pixel * 0.5 + Vec3::new(0.5, 0.5, 0.5),
+                Aov::Position => *pixel * (0.5 / max) + Vec3::new(0.5, 0.5, 0.5),
+                Aov::Albedo => *pixel,
+                Aov::ObjectId | Aov::MaterialId => id_color(pixel.x() as u32),
+            };
+        }
+        image
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Aov::ALL
+            .into_iter()
+            .find(|aov| aov.to_string() == s)
+            .ok_or_else(|| format!("unknown AOV `{}`", s))
+    }
+}
+
+impl fmt::Display for Aov {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Aov::Depth => write!(f, "depth"),
+            Aov::Normal => write!(f, "normal"),
+            Aov::Albedo => write!(f, "albedo"),
+            Aov::ObjectId => write!(f, "object_id"),
+            Aov::MaterialId => write!(f, "material_id"),
+            Aov::Position => write!(f, "position"),
+        }
+    }
+}
+
+// A saturated color for every ID, black for 0. Successive IDs step around
+// the hue circle by the golden angle, so that neighbours differ clearly.
+fn id_color(id: u32) -> Vec3 {
+    if id == 0 {
+        return Vec3::default();
+    }
+    let hue = (id as f32 * 0.618_034).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    match hue as u32 {
+        0 => Vec3::new(1.0, x, 0.0),
+        1 => Vec3::new(x, 1.0, 0.0),
+        2 => Vec3::new(0.0, 1.0, x),
+        3 => Vec3::new(0.0, x, 1.0),
+        4 => Vec3::new(x, 0.0, 1.0),
+        _ => Vec3::new(1.0, 0.0, x),
+    }
+}
+
+/// The images of a set of AOVs, all of the same size.
+#[derive(Debug, Clone)]
+pub struct Aovs {
+    buffers: Vec<(Aov, Framebuffer)>,
+}
+
+impl Aovs {
+    pub fn new(aovs: &[Aov], width: usize, height: usize) -> Self {
+        let mut buffers: Vec<(Aov, Framebuffer)> = Vec::new();
+        for &aov in aovs {
+            if buffers.iter().all(|(other, _)| *other != aov) {
+                buffers.push((aov, Framebuffer::new(width, height)));
+            }
+        }
+        Self { buffers }
+    }
+
+    pub fn get(&self, aov: Aov) -> Option<&Framebuffer> {
+        self.buffers
+            .iter()
+            .find(|(other, _)| *other == aov)
+            .map(|(_, framebuffer)| framebuffer)
+    }
+
+    pub fn buffers(&self) -> &[(Aov, Framebuffer)] {
+        &self.buffers
+    }
+
+    pub fn buffers_mut(&mut self) -> &mut [(Aov, Framebuffer)] {
+        &mut self.buffers
+    }
+
+    /// Writes every AOV next to the image at `path`, e.g. `image.depth.png`
+    /// for `image.png`. HDR formats keep the values as they are, 8 bit
+    /// formats get them visualized.
+    pub fn save(&self, path: &Path) -> Result<(), OutputError> {
+        let format = OutputFormat::from_path(path)
+            .ok_or_else(|| OutputError::UnsupportedFormat(path.into()))?;
+        for (aov, framebuffer) in &self.buffers {
+            let path = aov_path(path, *aov);
+            if format.is_hdr() {
+                output::save(framebuffer, &path, ToneMapping::Clamp, 0.0)?;
+            } else {
+                output::save(&aov.visualize(framebuffer), &path, ToneMapping::Clamp, 0.0)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Path of the image of `aov` that goes with the image at `path`.
+pub fn aov_path(path: &Path, aov: Aov) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, aov, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, aov),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::aov::{aov_path, Aov, Aovs};
+    use crate::film::framebuffer::Framebuffer;
+    use crate::vec3::Vec3;
+    use std::path::Path;
+
+    #[test]
+    fn test_aov_names() {
+        for aov in Aov::ALL {
+            assert_eq!(Ok(aov), aov.to_string().parse());
+        }
+        assert_eq!(Ok(Aov::ObjectId), "object_id".parse());
+        assert!("beauty".parse::<Aov>().is_err());
+        assert_eq!(
+            Path::new("out/image.depth.exr"),
+            aov_path(Path::new("out/image.exr"), Aov::Depth)
+        );
+    }
+
+    #[test]
+    fn test_aov_visualize() {
+        let mut framebuffer = Framebuffer::new(3, 1);
+        framebuffer.set(0, 0, Vec3::new(0.0, 0.0, -1.0));
+        framebuffer.set(1, 0, Vec3::new(2.0, 2.0, 2.0));
+        framebuffer.set(2, 0, Vec3::new(3.0, 3.0, 3.0));
+
+        let normal = Aov::Normal.visualize(&framebuffer);
+        assert_eq!(Vec3::new(0.5, 0.5, 0.0), normal.get(0, 0));
+        let depth = Aov::Depth.visualize(&framebuffer);
+        assert_eq!(Vec3::new(1.0, 1.0, 1.0), depth.get(2, 0));
+        let ids = Aov::ObjectId.visualize(&framebuffer);
+        assert_eq!(Vec3::default(), ids.get(0, 0));
+        assert_ne!(ids.get(1, 0), ids.get(2, 0));
+    }
+
+    #[test]
+    fn test_aovs() {
+        let aovs = Aovs::new(&[Aov::Depth, Aov::Albedo, Aov::Depth], 4, 2);
+        assert_eq!(2, aovs.buffers().len());
+        assert_eq!(4, aovs.get(Aov::Albedo).unwrap().width());
+        assert!(aovs.get(Aov::Normal).is_none());
+    }
+}
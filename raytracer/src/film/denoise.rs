@@ -0,0 +1,244 @@
+use crate::film::framebuffer::Framebuffer;
+use crate::vec3::Vec3;
+use rayon::prelude::*;
+
+// Weights of the B3 spline, the 1D kernel of every wavelet level.
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al. 2010). Every
+/// iteration blurs with a 5x5 kernel whose taps are spread twice as far as
+/// in the previous one, and taps that differ from the center pixel in color,
+/// normal or albedo get less weight, so that edges and textures stay sharp
+/// while the noise in between is smoothed away.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    iterations: usize,
+    sigma_color: f32,
+    sigma_normal: f32,
+    sigma_albedo: f32,
+}
+
+impl Denoiser {
+    pub fn new(iterations: usize, sigma_color: f32, sigma_normal: f32, sigma_albedo: f32) -> Self {
+        Self {
+            iterations,
+            sigma_color,
+            sigma_normal,
+            sigma_albedo,
+        }
+    }
+
+    /// Number of wavelet levels, the last one reaches `2^iterations` pixels
+    /// far. Levels whose taps would all fall outside the image are skipped.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Tolerated difference in illumination, halved every iteration.
+    pub fn sigma_color(&self) -> f32 {
+        self.sigma_color
+    }
+
+    pub fn sigma_normal(&self) -> f32 {
+        self.sigma_normal
+    }
+
+    pub fn sigma_albedo(&self) -> f32 {
+        self.sigma_albedo
+    }
+
+    pub fn set_iterations(&mut self, iterations: usize) {
+        self.iterations = iterations;
+    }
+
+    pub fn set_sigma_color(&mut self, sigma_color: f32) {
+        self.sigma_color = sigma_color;
+    }
+
+    pub fn set_sigma_normal(&mut self, sigma_normal: f32) {
+        self.sigma_normal = sigma_normal;
+    }
+
+    pub fn set_sigma_albedo(&mut self, sigma_albedo: f32) {
+        self.sigma_albedo = sigma_albedo;
+    }
+
+    /// Filters `color` guided by the `albedo` and `normal` buffers of the
+    /// same image, e.g. the `Albedo` and `Normal` AOVs. The illumination is
+    /// filtered on its own and multiplied by the albedo again, which keeps
+    /// textures out of the blur.
+    ///
+    /// Panics if the buffers differ in size.
+    pub fn denoise(
+        &self,
+        color: &Framebuffer,
+        albedo: &Framebuffer,
+        normal: &Framebuffer,
+    ) -> Framebuffer {
+        let (width, height) = (color.width(), color.height());
+        assert!(
+            (albedo.width(), albedo.height()) == (width, height)
+                && (normal.width(), normal.height()) == (width, height),
+            "guide buffers must match the image size"
+        );
+
+        // Channels without albedo, e.g. where nothing is hit, are filtered
+        // as they are.
+        let divisor = |a: f32| if a > 1e-3 { a } else { 1.0 };
+        let demodulate = |c: Vec3, a: Vec3| {
+            Vec3::new(
+                c.x() / divisor(a.x()),
+                c.y() / divisor(a.y()),
+                c.z() / divisor(a.z()),
+            )
+        };
+        let modulate = |c: Vec3, a: Vec3| {
+            Vec3::new(
+                c.x() * divisor(a.x()),
+                c.y() * divisor(a.y()),
+                c.z() * divisor(a.z()),
+            )
+        };
+
+        let (albedo, normal) = (albedo.pixels(), normal.pixels());
+        let mut light: Vec<Vec3> = color
+            .pixels()
+            .iter()
+            .zip(albedo)
+            .map(|(&c, &a)| demodulate(c, a))
+            .collect();
+        let mut filtered = vec![Vec3::default(); light.len()];
+
+        for iteration in 0..self.iterations {
+            let step = 1 << iteration;
+            // Only the center tap is left inside the image from here on.
+            if step >= width.max(height) as isize {
+                break;
+            }
+            let sigma_color = self.sigma_color / step as f32;
+            let weight = |sigma: f32, d: Vec3| (-(d * d) / (sigma * sigma).max(1e-12)).exp();
+            filtered
+                .par_chunks_mut(width.max(1))
+                .enumerate()
+                .for_each(|(y, row)| {
+                    for (x, out) in row.iter_mut().enumerate() {
+                        let p = y * width + x;
+                        let mut sum = Vec3::default();
+                        let mut total = 0.0;
+                        for (j, ky) in KERNEL.iter().enumerate() {
+                            let qy = y as isize + (j as isize - 2) * step;
+                            if qy < 0 || qy >= height as isize {
+                                continue;
+                            }
+                            for (i, kx) in KERNEL.iter().enumerate() {
+                                let qx = x as isize + (i as isize - 2) * step;
+                                if qx < 0 || qx >= width as isize {
+                                    continue;
+                                }
+                                let q = qy as usize * width + qx as usize;
+                                let w = kx
+                                    * ky
+                                    * weight(sigma_color, light[p] - light[q])
+                                    * weight(self.sigma_normal, normal[p] - normal[q])
+                                    * weight(self.sigma_albedo, albedo[p] - albedo[q]);
+                                sum = sum + light[q] * w;
+                                total += w;
+                            }
+                        }
+                        // The center tap always has a positive weight.
+                        *out = sum * (1.0 / total);
+                    }
+                });
+            std::mem::swap(&mut light, &mut filtered);
+        }
+
+        let mut image = Framebuffer::new(width, height);
+        for ((pixel, &c), &a) in image.pixels_mut().iter_mut().zip(&light).zip(albedo) {
+            *pixel = modulate(c, a);
+        }
+        image
+    }
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new(5, 4.0, 0.5, 0.1)
+    }
+}
+
+/// Filters `color` with the default settings of `Denoiser`.
+pub fn denoise(color: &Framebuffer, albedo: &Framebuffer, normal: &Framebuffer) -> Framebuffer {
+    Denoiser::default().denoise(color, albedo, normal)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::denoise::{denoise, Denoiser};
+    use crate::film::framebuffer::Framebuffer;
+    use crate::vec3::Vec3;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg32;
+
+    fn filled(width: usize, height: usize, value: Vec3) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(width, height);
+        framebuffer.pixels_mut().fill(value);
+        framebuffer
+    }
+
+    fn variance(pixels: &[Vec3]) -> f32 {
+        let mean = pixels.iter().map(|p| p.x()).sum::<f32>() / pixels.len() as f32;
+        pixels.iter().map(|p| (p.x() - mean).powi(2)).sum::<f32>() / pixels.len() as f32
+    }
+
+    #[test]
+    fn test_denoise_smooths_noise() {
+        let mut rng = Pcg32::seed_from_u64(7);
+        let mut color = Framebuffer::new(32, 32);
+        for pixel in color.pixels_mut() {
+            let value = 0.5 + rng.gen_range(-0.2..0.2);
+            *pixel = Vec3::new(value, value, value);
+        }
+        let albedo = filled(32, 32, Vec3::new(1.0, 1.0, 1.0));
+        let normal = filled(32, 32, Vec3::new(0.0, 0.0, 1.0));
+
+        let image = denoise(&color, &albedo, &normal);
+        assert!(variance(image.pixels()) < variance(color.pixels()) / 10.0);
+
+        // Levels beyond the image size leave the result as it is.
+        let mut deep = Denoiser::default();
+        deep.set_iterations(64);
+        let mut enough = Denoiser::default();
+        enough.set_iterations(6);
+        assert_eq!(
+            enough.denoise(&color, &albedo, &normal).pixels(),
+            deep.denoise(&color, &albedo, &normal).pixels()
+        );
+
+        let mut off = Denoiser::default();
+        off.set_iterations(0);
+        assert_eq!(
+            color.pixels(),
+            off.denoise(&color, &albedo, &normal).pixels()
+        );
+    }
+
+    #[test]
+    fn test_denoise_keeps_edges() {
+        // Two surfaces facing different ways, lit alike but of different color.
+        let mut color = filled(16, 8, Vec3::new(0.5, 0.5, 0.5));
+        let mut albedo = filled(16, 8, Vec3::new(0.5, 0.5, 0.5));
+        let mut normal = filled(16, 8, Vec3::new(0.0, 0.0, 1.0));
+        for y in 0..8 {
+            for x in 8..16 {
+                color.set(x, y, Vec3::new(1.0, 0.0, 0.0));
+                albedo.set(x, y, Vec3::new(1.0, 0.0, 0.0));
+                normal.set(x, y, Vec3::new(0.0, 1.0, 0.0));
+            }
+        }
+
+        let image = denoise(&color, &albedo, &normal);
+        for (x, expected) in [(7, color.get(7, 0)), (8, color.get(8, 0))] {
+            assert!((image.get(x, 4) - expected).length() < 1e-3);
+        }
+    }
+}
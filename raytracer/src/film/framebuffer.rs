@@ -0,0 +1,99 @@
+use crate::film::tonemap::{linear_to_srgb, ToneMapping};
+use crate::vec3::Vec3;
+
+/// Linear floating point radiance of a rendered image, row by row from the
+/// top left pixel.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Vec3::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &Vec<Vec3> {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut Vec<Vec3> {
+        &mut self.pixels
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Vec3 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Vec3) {
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Scales the radiance by `2^exposure`, applies `tone_mapping` and encodes
+    /// the result as 8 bit sRGB.
+    pub fn to_rgb8(&self, tone_mapping: ToneMapping, exposure: f32) -> Vec<u8> {
+        let scale = exposure.exp2();
+        self.pixels
+            .iter()
+            .flat_map(|&pixel| {
+                let c = tone_mapping.apply(pixel * scale);
+                [c.x(), c.y(), c.z()].map(|x| (linear_to_srgb(x) * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    /// Linear radiance scaled by `2^exposure`, three floats per pixel.
+    pub fn to_rgb32f(&self, exposure: f32) -> Vec<f32> {
+        let scale = exposure.exp2();
+        self.pixels
+            .iter()
+            .flat_map(|&pixel| {
+                let c = pixel * scale;
+                [c.x(), c.y(), c.z()]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::framebuffer::Framebuffer;
+    use crate::film::tonemap::ToneMapping;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_framebuffer_to_rgb8() {
+        let mut framebuffer = Framebuffer::new(2, 1);
+        framebuffer.set(0, 0, Vec3::new(0.0, 0.5, 16.0));
+        framebuffer.set(1, 0, Vec3::new(0.25, 1.0, -1.0));
+        assert_eq!(Vec3::new(0.25, 1.0, -1.0), framebuffer.get(1, 0));
+
+        assert_eq!(
+            vec![0, 188, 255, 137, 255, 0],
+            framebuffer.to_rgb8(ToneMapping::Clamp, 0.0)
+        );
+        // One stop up doubles the radiance.
+        assert_eq!(
+            framebuffer.to_rgb8(ToneMapping::Clamp, 0.0)[1],
+            framebuffer.to_rgb8(ToneMapping::Clamp, 1.0)[3]
+        );
+        assert_eq!(
+            vec![0.0, 1.0, 32.0, 0.5, 2.0, -2.0],
+            framebuffer.to_rgb32f(1.0)
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod aov;
+pub mod denoise;
+pub mod framebuffer;
+pub mod output;
+pub mod tonemap;
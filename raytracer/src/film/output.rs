@@ -0,0 +1,256 @@
+use crate::film::framebuffer::Framebuffer;
+use crate::film::tonemap::ToneMapping;
+use image::codecs::hdr::HdrEncoder;
+use image::{ImageError, ImageFormat, Rgb};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum OutputError {
+    UnsupportedFormat(PathBuf),
+    Io(io::Error),
+    Image(ImageError),
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputError::UnsupportedFormat(path) => write!(
+                f,
+                "{}: unsupported image format, use e.g. .png, .jpg, .exr, .hdr or .pfm",
+                path.display()
+            ),
+            OutputError::Io(err) => write!(f, "{}", err),
+            OutputError::Image(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for OutputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OutputError::UnsupportedFormat(_) => None,
+            OutputError::Io(err) => Some(err),
+            OutputError::Image(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for OutputError {
+    fn from(err: io::Error) -> Self {
+        OutputError::Io(err)
+    }
+}
+
+impl From<ImageError> for OutputError {
+    fn from(err: ImageError) -> Self {
+        OutputError::Image(err)
+    }
+}
+
+/// Image file format, picked from the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 8 bit formats written by the `image` crate, tone mapped to sRGB.
+    Ldr(ImageFormat),
+    /// OpenEXR with linear 32 bit float channels.
+    Exr,
+    /// Radiance RGBE.
+    Hdr,
+    /// Portable float map.
+    Pfm,
+}
+
+impl OutputFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "exr" => Some(OutputFormat::Exr),
+            "hdr" => Some(OutputFormat::Hdr),
+            "pfm" => Some(OutputFormat::Pfm),
+            _ => match ImageFormat::from_extension(extension) {
+                Some(format) if format.can_write() => Some(OutputFormat::Ldr(format)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Whether the format stores linear radiance instead of tone mapped colors.
+    pub fn is_hdr(self) -> bool {
+        !matches!(self, OutputFormat::Ldr(_))
+    }
+}
+
+/// Writes the framebuffer to `path`. Every format is scaled by `2^exposure`,
+/// 8 bit formats are then tone mapped and sRGB encoded while the HDR formats
+/// keep linear radiance.
+pub fn save(
+    framebuffer: &Framebuffer,
+    path: &Path,
+    tone_mapping: ToneMapping,
+    exposure: f32,
+) -> Result<(), OutputError> {
+    let format =
+        OutputFormat::from_path(path).ok_or_else(|| OutputError::UnsupportedFormat(path.into()))?;
+    let width = framebuffer.width() as u32;
+    let height = framebuffer.height() as u32;
+
+    match format {
+        OutputFormat::Ldr(format) => image::save_buffer_with_format(
+            path,
+            &framebuffer.to_rgb8(tone_mapping, exposure),
+            width,
+            height,
+            image::ColorType::Rgb8,
+            format,
+        )?,
+        OutputFormat::Exr => {
+            image::Rgb32FImage::from_raw(width, height, framebuffer.to_rgb32f(exposure))
+                .expect("framebuffer holds width * height pixels")
+                .save_with_format(path, ImageFormat::OpenExr)?
+        }
+        OutputFormat::Hdr => {
+            let pixels: Vec<Rgb<f32>> = framebuffer
+                .to_rgb32f(exposure)
+                .chunks(3)
+                .map(|c| Rgb([c[0], c[1], c[2]]))
+                .collect();
+            HdrEncoder::new(BufWriter::new(File::create(path)?)).encode(
+                &pixels,
+                framebuffer.width(),
+                framebuffer.height(),
+            )?
+        }
+        OutputFormat::Pfm => write_pfm(framebuffer, exposure, File::create(path)?)?,
+    }
+
+    Ok(())
+}
+
+/// Path of frame `frame` of an image sequence, e.g. `frame_0001.png` for
+/// frame 1 of `frame.png`.
+pub fn frame_path(path: &Path, frame: u32) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{}_{:04}.{}", stem, frame, extension.to_string_lossy()),
+        None => format!("{}_{:04}", stem, frame),
+    };
+    path.with_file_name(name)
+}
+
+// Little endian PFM, whose rows go from the bottom to the top.
+fn write_pfm<W: Write>(framebuffer: &Framebuffer, exposure: f32, out: W) -> io::Result<()> {
+    let mut out = BufWriter::new(out);
+    write!(
+        out,
+        "PF\n{} {}\n-1.0\n",
+        framebuffer.width(),
+        framebuffer.height()
+    )?;
+    let data = framebuffer.to_rgb32f(exposure);
+    for row in data.chunks(3 * framebuffer.width().max(1)).rev() {
+        for value in row {
+            out.write_all(&value.to_le_bytes())?;
+        }
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::framebuffer::Framebuffer;
+    use crate::film::output::{frame_path, save, write_pfm, OutputError, OutputFormat};
+    use crate::film::tonemap::ToneMapping;
+    use crate::vec3::Vec3;
+    use image::codecs::hdr::HdrDecoder;
+    use image::ImageFormat;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::Path;
+
+    fn framebuffer() -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(2, 2);
+        framebuffer.set(0, 0, Vec3::new(4.0, 0.5, 0.0));
+        framebuffer.set(1, 1, Vec3::new(0.0, 0.25, 1.0));
+        framebuffer
+    }
+
+    #[test]
+    fn test_output_format() {
+        let format = |path: &str| OutputFormat::from_path(Path::new(path));
+        assert_eq!(Some(OutputFormat::Ldr(ImageFormat::Png)), format("a.png"));
+        assert_eq!(Some(OutputFormat::Exr), format("a.EXR"));
+        assert_eq!(Some(OutputFormat::Hdr), format("dir/a.hdr"));
+        assert_eq!(Some(OutputFormat::Pfm), format("a.pfm"));
+        assert_eq!(None, format("a.txt"));
+        assert_eq!(None, format("png"));
+        assert!(!OutputFormat::Ldr(ImageFormat::Png).is_hdr());
+        assert!(OutputFormat::Pfm.is_hdr());
+    }
+
+    #[test]
+    fn test_frame_path() {
+        assert_eq!(
+            Path::new("out/frame_0001.png"),
+            frame_path(Path::new("out/frame.png"), 1)
+        );
+        assert_eq!(Path::new("a_12345"), frame_path(Path::new("a"), 12345));
+    }
+
+    #[test]
+    fn test_write_pfm() {
+        let mut data = Vec::new();
+        write_pfm(&framebuffer(), 0.0, &mut data).unwrap();
+        let header = b"PF\n2 2\n-1.0\n";
+        assert_eq!(header, &data[..header.len()]);
+        let floats: Vec<f32> = data[header.len()..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        // Bottom row first.
+        assert_eq!(
+            vec![0.0, 0.0, 0.0, 0.0, 0.25, 1.0, 4.0, 0.5, 0.0, 0.0, 0.0, 0.0],
+            floats
+        );
+    }
+
+    #[test]
+    fn test_save() {
+        let dir = std::env::temp_dir().join("raytracer_test_save");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for name in ["out.png", "out.exr"] {
+            let path = dir.join(name);
+            save(&framebuffer(), &path, ToneMapping::Reinhard, 0.0).unwrap();
+            let image = image::open(&path).unwrap().into_rgb32f();
+            assert_eq!((2, 2), image.dimensions());
+            let red = image.get_pixel(0, 0)[0];
+            match name {
+                // Reinhard maps 4 to 0.8, which is 0.906 in sRGB.
+                "out.png" => assert!((red - 0.906).abs() < 0.005, "{}", red),
+                _ => assert_eq!(4.0, red),
+            }
+        }
+
+        // `image::open` would convert Radiance files to 8 bit.
+        let path = dir.join("out.hdr");
+        save(&framebuffer(), &path, ToneMapping::Reinhard, 1.0).unwrap();
+        let file = BufReader::new(File::open(&path).unwrap());
+        let pixels = HdrDecoder::new(file).unwrap().read_image_hdr().unwrap();
+        assert_eq!(4, pixels.len());
+        assert!((pixels[0][0] - 8.0).abs() < 0.05, "{:?}", pixels[0]);
+
+        assert!(matches!(
+            save(
+                &framebuffer(),
+                &dir.join("out.txt"),
+                ToneMapping::Clamp,
+                0.0
+            ),
+            Err(OutputError::UnsupportedFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::vec3::Vec3;
+use std::fmt;
+use std::str::FromStr;
+
+/// Operator that compresses linear radiance into the displayable `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapping {
+    /// Cuts every channel off at 1.
+    #[default]
+    Clamp,
+    /// `c / (1 + c)`, keeps detail in highlights but flattens contrast.
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve.
+    Aces,
+}
+
+impl ToneMapping {
+    pub fn apply(self, color: Vec3) -> Vec3 {
+        let map = |x: f32| -> f32 {
+            // NaNs from degenerate samples end up black.
+            let x = if x.is_nan() { 0.0 } else { x.max(0.0) };
+            match self {
+                ToneMapping::Clamp => x.min(1.0),
+                ToneMapping::Reinhard => x / (1.0 + x),
+                ToneMapping::Aces => {
+                    ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+                }
+            }
+        };
+        Vec3::new(map(color.x()), map(color.y()), map(color.z()))
+    }
+}
+
+impl FromStr for ToneMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clamp" => Ok(ToneMapping::Clamp),
+            "reinhard" => Ok(ToneMapping::Reinhard),
+            "aces" => Ok(ToneMapping::Aces),
+            _ => Err(format!("unknown tone mapping `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for ToneMapping {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ToneMapping::Clamp => write!(f, "clamp"),
+            ToneMapping::Reinhard => write!(f, "reinhard"),
+            ToneMapping::Aces => write!(f, "aces"),
+        }
+    }
+}
+
+/// Encodes a linear value in `[0, 1]` with the sRGB transfer function.
+pub fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Inverse of `linear_to_srgb`.
+pub fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::tonemap::{linear_to_srgb, srgb_to_linear, ToneMapping};
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_srgb() {
+        assert_eq!(0.0, linear_to_srgb(0.0));
+        assert!((linear_to_srgb(1.0) - 1.0).abs() < 1e-6);
+        assert!((linear_to_srgb(0.18) - 0.4614).abs() < 1e-3);
+        for x in [0.001, 0.2, 0.5, 0.9] {
+            assert!((srgb_to_linear(linear_to_srgb(x)) - x).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_tone_mapping() {
+        let bright = Vec3::new(0.5, 4.0, f32::NAN);
+        assert_eq!(Vec3::new(0.5, 1.0, 0.0), ToneMapping::Clamp.apply(bright));
+        assert_eq!(
+            Vec3::new(0.5 / 1.5, 0.8, 0.0),
+            ToneMapping::Reinhard.apply(bright)
+        );
+
+        let mut last = 0.0;
+        for i in 1..20 {
+            let y = ToneMapping::Aces
+                .apply(Vec3::new(i as f32 * 0.2, 0.0, 0.0))
+                .x();
+            assert!(y > last && y <= 1.0);
+            last = y;
+        }
+        assert_eq!(0.0, ToneMapping::Aces.apply(Vec3::default()).x());
+        assert_eq!(1.0, ToneMapping::Aces.apply(Vec3::new(100.0, 0.0, 0.0)).x());
+    }
+}
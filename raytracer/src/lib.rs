@@ -1,4 +1,14 @@
+pub mod animation;
+pub mod backgrounds;
+pub mod camera;
+pub mod film;
+pub mod lights;
+pub mod mat4;
 pub mod material;
+pub mod media;
 pub mod objects;
+pub mod ray;
 pub mod raytracing;
+pub mod scene;
+pub mod textures;
 pub mod vec3;
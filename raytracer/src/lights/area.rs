@@ -0,0 +1,175 @@
+use crate::lights::light::{Light, LightSample};
+use crate::raytracing::sampling::{stratified_offsets, uniform_cone};
+use crate::vec3::Vec3;
+use rand::RngCore;
+
+/// A one-sided rectangular light spanned by `edge1` and `edge2` from
+/// `corner`. It shines towards `edge1 x edge2` and is sampled with
+/// `samples` stratified shadow rays, which gives soft shadows.
+#[derive(Clone)]
+pub struct RectLight {
+    corner: Vec3,
+    edge1: Vec3,
+    edge2: Vec3,
+    normal: Vec3,
+    color: Vec3,
+    intensity: f32,
+    samples: usize,
+}
+
+impl RectLight {
+    pub fn new(
+        corner: Vec3,
+        edge1: Vec3,
+        edge2: Vec3,
+        color: Vec3,
+        intensity: f32,
+        samples: usize,
+    ) -> Self {
+        Self {
+            corner,
+            edge1,
+            edge2,
+            normal: edge1.cross(edge2).norm(),
+            color,
+            intensity,
+            samples: samples.max(1),
+        }
+    }
+}
+
+impl Light for RectLight {
+    fn samples(&self, point: Vec3, rng: &mut dyn RngCore) -> Vec<LightSample> {
+        let share = self.intensity / self.samples as f32;
+        stratified_offsets(self.samples, rng)
+            .into_iter()
+            .filter_map(|(u, v)| {
+                let to_light = self.corner + self.edge1 * u + self.edge2 * v - point;
+                let direction = to_light.norm();
+                // Lambertian emitter: dimmer when seen at a grazing angle.
+                let cos = -direction * self.normal;
+                if cos <= 0.0 {
+                    return None;
+                }
+                Some(LightSample {
+                    direction,
+                    distance: to_light.length(),
+                    intensity: self.color * (share * cos),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A spherical light sampled with `samples` shadow rays spread uniformly over
+/// the cone of directions it covers.
+#[derive(Clone)]
+pub struct SphereLight {
+    center: Vec3,
+    radius: f32,
+    color: Vec3,
+    intensity: f32,
+    samples: usize,
+}
+
+impl SphereLight {
+    pub fn new(center: Vec3, radius: f32, color: Vec3, intensity: f32, samples: usize) -> Self {
+        Self {
+            center,
+            radius,
+            color,
+            intensity,
+            samples: samples.max(1),
+        }
+    }
+}
+
+impl Light for SphereLight {
+    fn samples(&self, point: Vec3, rng: &mut dyn RngCore) -> Vec<LightSample> {
+        let to_center = self.center - point;
+        let distance = to_center.length();
+        if distance <= self.radius {
+            return Vec::new();
+        }
+        let axis = to_center * (1.0 / distance);
+        let cos_max = (1.0 - (self.radius / distance).powi(2)).sqrt();
+        let share = self.intensity / self.samples as f32;
+
+        (0..self.samples)
+            .map(|_| {
+                let direction = uniform_cone(axis, cos_max, rng);
+                // Distance to the near side of the sphere along `direction`.
+                let tca = to_center * direction;
+                let d2 = distance * distance - tca * tca;
+                let distance = tca - (self.radius * self.radius - d2).max(0.0).sqrt();
+                LightSample {
+                    direction,
+                    distance,
+                    intensity: self.color * share,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lights::area::{RectLight, SphereLight};
+    use crate::lights::light::Light;
+    use crate::raytracing::sampling::pixel_rng;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_rect_light_samples() {
+        let light = RectLight::new(
+            Vec3::new(-1.0, 5.0, -1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.5, 0.25),
+            4.0,
+            16,
+        );
+        let mut rng = pixel_rng(1, 2);
+
+        let samples = light.samples(Vec3::default(), &mut rng);
+        assert_eq!(16, samples.len());
+        let total = samples
+            .iter()
+            .fold(Vec3::default(), |sum, s| sum + s.intensity);
+        assert!(total.x() > 3.5 && total.x() < 4.0);
+        assert!((total.y() - total.x() / 2.0).abs() < 1e-5);
+        for s in samples {
+            assert!(s.direction.y() > 0.9);
+            assert!(s.distance >= 5.0 && s.distance < 5.2);
+        }
+
+        // The back side does not emit.
+        assert!(light
+            .samples(Vec3::new(0.0, 10.0, 0.0), &mut rng)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_sphere_light_samples() {
+        let light = SphereLight::new(
+            Vec3::new(0.0, 0.0, -10.0),
+            1.0,
+            Vec3::new(1.0, 1.0, 1.0),
+            2.0,
+            8,
+        );
+        let mut rng = pixel_rng(1, 2);
+
+        let samples = light.samples(Vec3::default(), &mut rng);
+        assert_eq!(8, samples.len());
+        for s in samples {
+            assert_eq!(Vec3::new(0.25, 0.25, 0.25), s.intensity);
+            let p = s.direction * s.distance;
+            assert!(((p - Vec3::new(0.0, 0.0, -10.0)).length() - 1.0).abs() < 1e-3);
+            assert!(s.distance <= 10.0);
+        }
+        assert!(light
+            .samples(Vec3::new(0.0, 0.0, -10.5), &mut rng)
+            .is_empty());
+    }
+}
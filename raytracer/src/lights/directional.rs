@@ -0,0 +1,32 @@
+use crate::lights::light::{Light, LightSample};
+use crate::vec3::Vec3;
+use rand::RngCore;
+
+/// Light from an infinitely distant source such as the sun, arriving along
+/// `direction` everywhere in the scene.
+#[derive(Clone)]
+pub struct DirectionalLight {
+    direction: Vec3,
+    color: Vec3,
+    intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self {
+            direction: direction.norm(),
+            color,
+            intensity,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn samples(&self, _point: Vec3, _rng: &mut dyn RngCore) -> Vec<LightSample> {
+        vec![LightSample {
+            direction: -self.direction,
+            distance: f32::INFINITY,
+            intensity: self.color * self.intensity,
+        }]
+    }
+}
@@ -0,0 +1,21 @@
+use crate::vec3::Vec3;
+use rand::RngCore;
+
+/// Light arriving at a point from one sample of a light source.
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    /// Unit vector from the lit point towards the sample.
+    pub direction: Vec3,
+    /// Distance to the sample, infinite for directional lights.
+    pub distance: f32,
+    /// Colored intensity carried by this sample, not yet weighted by the
+    /// cosine at the lit surface.
+    pub intensity: Vec3,
+}
+
+pub trait Light {
+    /// Returns the samples through which the light reaches `point`. Each one
+    /// needs its own shadow ray, their contributions add up to the light's
+    /// total, so area lights split their intensity between their samples.
+    fn samples(&self, point: Vec3, rng: &mut dyn RngCore) -> Vec<LightSample>;
+}
@@ -0,0 +1,5 @@
+pub mod area;
+pub mod directional;
+pub mod light;
+pub mod point;
+pub mod spot;
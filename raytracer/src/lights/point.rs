@@ -0,0 +1,34 @@
+use crate::lights::light::{Light, LightSample};
+use crate::vec3::Vec3;
+use rand::RngCore;
+
+#[derive(Clone)]
+pub struct PointLight {
+    position: Vec3,
+    color: Vec3,
+    intensity: f32,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, color: Vec3, intensity: f32) -> Self {
+        Self {
+            position,
+            color,
+            intensity,
+        }
+    }
+
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+}
+
+impl Light for PointLight {
+    fn samples(&self, point: Vec3, _rng: &mut dyn RngCore) -> Vec<LightSample> {
+        vec![LightSample {
+            direction: (self.position - point).norm(),
+            distance: (self.position - point).length(),
+            intensity: self.color * self.intensity,
+        }]
+    }
+}
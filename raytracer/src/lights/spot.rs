@@ -0,0 +1,96 @@
+use crate::lights::light::{Light, LightSample};
+use crate::vec3::Vec3;
+use rand::RngCore;
+
+/// A point light restricted to a cone. Inside `inner_angle` the light has
+/// full intensity, between it and `outer_angle` it fades out smoothly.
+#[derive(Clone)]
+pub struct SpotLight {
+    position: Vec3,
+    direction: Vec3,
+    cos_inner: f32,
+    cos_outer: f32,
+    color: Vec3,
+    intensity: f32,
+}
+
+impl SpotLight {
+    /// Angles are measured from the cone axis, in radians.
+    pub fn new(
+        position: Vec3,
+        direction: Vec3,
+        inner_angle: f32,
+        outer_angle: f32,
+        color: Vec3,
+        intensity: f32,
+    ) -> Self {
+        Self {
+            position,
+            direction: direction.norm(),
+            cos_inner: inner_angle.min(outer_angle).cos(),
+            cos_outer: outer_angle.cos(),
+            color,
+            intensity,
+        }
+    }
+
+    fn falloff(&self, cos: f32) -> f32 {
+        if cos >= self.cos_inner {
+            return 1.0;
+        }
+        if cos <= self.cos_outer {
+            return 0.0;
+        }
+        let x = (cos - self.cos_outer) / (self.cos_inner - self.cos_outer);
+        x * x * (3.0 - 2.0 * x)
+    }
+}
+
+impl Light for SpotLight {
+    fn samples(&self, point: Vec3, _rng: &mut dyn RngCore) -> Vec<LightSample> {
+        let direction = (self.position - point).norm();
+        let falloff = self.falloff(-direction * self.direction);
+        if falloff == 0.0 {
+            return Vec::new();
+        }
+        vec![LightSample {
+            direction,
+            distance: (self.position - point).length(),
+            intensity: self.color * (self.intensity * falloff),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lights::light::Light;
+    use crate::lights::spot::SpotLight;
+    use crate::raytracing::sampling::pixel_rng;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_spot_light_cone() {
+        let light = SpotLight::new(
+            Vec3::new(0.0, 10.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            0.2,
+            0.4,
+            Vec3::new(1.0, 1.0, 1.0),
+            2.0,
+        );
+        let mut rng = pixel_rng(0, 0);
+
+        let center = light.samples(Vec3::default(), &mut rng);
+        assert_eq!(1, center.len());
+        assert_eq!(Vec3::new(2.0, 2.0, 2.0), center[0].intensity);
+        assert_eq!(10.0, center[0].distance);
+
+        let edge = light.samples(Vec3::new(3.0, 0.0, 0.0), &mut rng);
+        assert!(edge[0].intensity.x() > 0.0 && edge[0].intensity.x() < 2.0);
+
+        assert!(light.samples(Vec3::new(5.0, 0.0, 0.0), &mut rng).is_empty());
+        assert!(light
+            .samples(Vec3::new(0.0, 20.0, 0.0), &mut rng)
+            .is_empty());
+    }
+}
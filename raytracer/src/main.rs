@@ -1,84 +1,141 @@
-use rayon::prelude::*;
-use raytracer::material::Material;
-use raytracer::objects::object::Object;
-use raytracer::objects::plane::Plane;
-use raytracer::objects::sphere::Sphere;
-use raytracer::raytracing::physics::cast_ray;
-use raytracer::vec3::Vec3;
+mod cli;
+
+use clap::Parser;
+use cli::{Cli, Command, RenderArgs};
+use raytracer::film::aov::Aov;
+use raytracer::film::denoise::Denoiser;
+use raytracer::film::framebuffer::Framebuffer;
+use raytracer::film::output;
+use raytracer::raytracing::render::{Progress, Renderer};
+use raytracer::scene::Scene;
 use std::f32::consts::PI;
-use std::sync::Arc;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
 use std::time::Instant;
 
 fn main() {
-    let greenish = Material::new(1.0, [0.9, 0.5, 0.1, 0.0], Vec3::new(0.1, 0.4, 0.2), 120.0);
-    let glass = Material::new(1.5, [0.0, 0.9, 0.1, 0.8], Vec3::new(0.6, 0.7, 0.8), 125.0);
-    let red_rubber = Material::new(1.0, [1.4, 0.3, 0.0, 0.0], Vec3::new(0.3, 0.1, 0.1), 10.0);
-    let mirror = Material::new(1.0, [0.0, 16.0, 0.8, 0.0], Vec3::new(1.0, 1.0, 1.0), 1425.0);
+    let cli = Cli::parse();
 
-    let objects: Vec<Arc<dyn Object + Sync + Send>> = vec![
-        Arc::new(Sphere::new(Vec3::new(-4.0, 1.0, -16.0), 2.0, greenish)),
-        Arc::new(Sphere::new(Vec3::new(-1.0, -1.5, -12.0), 2.0, glass)),
-        Arc::new(Sphere::new(Vec3::new(4.0, -0.5, -18.0), 3.0, red_rubber)),
-        Arc::new(Sphere::new(Vec3::new(0.0, 12.0, -38.0), 10.0, mirror)),
-        Arc::new(Plane::new(
-            Vec3::new(0.0, -5.0, -15.0),
-            Vec3::new(0.0, 1.0, 0.0),
-            10.0,
-        )),
-    ];
+    match cli.command {
+        Command::Render(args) => render(args),
+    }
+}
 
-    let lights: Vec<Vec3> = vec![
-        Vec3::new(-20.0, 20.0, 20.0),
-        Vec3::new(30.0, 50.0, -25.0),
-        Vec3::new(30.0, 20.0, 30.0),
-    ];
+fn render(args: RenderArgs) {
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build_global()
+            .expect("global thread pool is initialized only once");
+    }
 
-    let scale_factor = 1;
+    match args.frames {
+        Some((first, last)) => {
+            let fps = load(&args, 0.0).fps();
+            for frame in first..=last {
+                println!("Frame {}", frame);
+                let scene = load(&args, frame as f32 / fps);
+                render_frame(&scene, &output::frame_path(&args.output, frame), &args);
+            }
+        }
+        None => render_frame(&load(&args, 0.0), &args.output, &args),
+    }
+}
 
-    let width = 3840 * scale_factor;
-    let height = 2160 * scale_factor;
-    let fov = (60.0 / 180.0) * PI;
+// Loads the scene at `time` with the settings of the command line applied.
+fn load(args: &RenderArgs, time: f32) -> Scene {
+    let mut scene = match Scene::from_file_at(&args.scene, time) {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("error: {}: {}", args.scene.display(), err);
+            process::exit(1);
+        }
+    };
 
-    let mut buffer: Vec<u8> = vec![0; width * height * 3];
+    if let Some(width) = args.width {
+        scene.set_width(width as usize);
+    }
+    if let Some(height) = args.height {
+        scene.set_height(height as usize);
+    }
+    if args.fov.is_some() || args.aperture.is_some() || args.focus_distance.is_some() {
+        let mut camera = scene.camera().clone();
+        if let Some(fov) = args.fov {
+            camera.set_fov((fov / 180.0) * PI);
+        }
+        if let Some(aperture) = args.aperture {
+            camera.set_aperture(aperture);
+        }
+        if let Some(focus_distance) = args.focus_distance {
+            camera.set_focus_distance(focus_distance);
+        }
+        scene.set_camera(camera);
+    }
+    if let Some(samples) = args.samples {
+        scene.set_samples(samples as usize);
+    }
+    if let Some(seed) = args.seed {
+        scene.set_seed(seed);
+    }
+    if let Some(integrator) = args.integrator {
+        scene.set_integrator(integrator.into());
+    }
+    if let Some(max_depth) = args.max_depth {
+        scene.set_max_depth(max_depth as i32);
+    }
+    scene
+}
 
+fn render_frame(scene: &Scene, path: &Path, args: &RenderArgs) {
     let start = Instant::now();
 
-    buffer
-        .par_iter_mut()
-        .chunks(3)
-        .enumerate()
-        .for_each(|(index, mut val)| {
-            let dir_x = ((index % width) as f32 + 0.5) - width as f32 / 2.0;
-            let dir_y = -((index / width) as f32 + 0.5) + height as f32 / 2.0;
-            let dir_z = -(height as f32) / (2.0 * (fov / 2.0).tan());
-
-            let vec = cast_ray(
-                Vec3::default(),
-                Vec3::new(dir_x, dir_y, dir_z).norm(),
-                &lights,
-                &objects,
-                0,
-            );
-
-            *val[0] = (vec.x() * 255.0) as u8;
-            *val[1] = (vec.y() * 255.0) as u8;
-            *val[2] = (vec.z() * 255.0) as u8;
-        });
+    let mut renderer = Renderer::new(scene, scene.camera());
+    renderer.set_passes(args.passes as usize);
+    renderer.set_tile_size(args.tile_size as usize);
+    renderer.set_denoiser(args.denoise.then(Denoiser::default));
+    let framebuffer = renderer.render(report, |preview, progress| {
+        // The final pass is saved below.
+        if progress.pass < progress.passes {
+            save(preview, path, args);
+        }
+    });
+    eprintln!();
 
     let duration = start.elapsed();
     println!("Time elapsed in raytracing: {:?}", duration);
 
-    // let start = Instant::now();
+    save(&framebuffer, path, args);
+
+    if !args.aov.is_empty() {
+        let aovs: Vec<Aov> = args.aov.iter().map(|&aov| aov.into()).collect();
+        if let Err(err) = renderer.render_aovs(&aovs).save(path) {
+            eprintln!("error: {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
 
-    image::save_buffer(
-        "image.png",
-        buffer.as_slice(),
-        width as u32,
-        height as u32,
-        image::ColorType::Rgb8,
-    )
-    .unwrap();
+fn report(progress: &Progress) {
+    let eta = match progress.eta() {
+        Some(eta) => format!("{}s", eta.as_secs()),
+        None => "-".to_string(),
+    };
+    let mut stderr = io::stderr().lock();
+    let _ = write!(
+        stderr,
+        "\rpass {}/{}, {:5.1}% done, {} left   ",
+        progress.pass,
+        progress.passes,
+        progress.fraction() * 100.0,
+        eta
+    );
+    let _ = stderr.flush();
+}
 
-    // let duration = start.elapsed();
-    // println!("Time elapsed in bufer: {:?}", duration);
+fn save(framebuffer: &Framebuffer, path: &Path, args: &RenderArgs) {
+    if let Err(err) = output::save(framebuffer, path, args.tonemap.into(), args.exposure) {
+        eprintln!("error: {}: {}", path.display(), err);
+        process::exit(1);
+    }
 }
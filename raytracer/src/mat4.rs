@@ -0,0 +1,230 @@
+use crate::vec3::Vec3;
+use std::ops::Mul;
+
+/// A 4x4 matrix in row-major order acting on column vectors, so that in
+/// `a * b` the transform `b` is applied first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    m: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const fn new(m: [[f32; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    pub const fn identity() -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        Self::new([
+            [1.0, 0.0, 0.0, offset.x()],
+            [0.0, 1.0, 0.0, offset.y()],
+            [0.0, 0.0, 1.0, offset.z()],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        Self::new([
+            [factors.x(), 0.0, 0.0, 0.0],
+            [0.0, factors.y(), 0.0, 0.0],
+            [0.0, 0.0, factors.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Counter-clockwise rotation by `angle` radians around `axis`, looking
+    /// from the tip of the axis towards the origin.
+    pub fn rotation(axis: Vec3, angle: f32) -> Self {
+        let a = axis.norm();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (sin, cos) = angle.sin_cos();
+        let t = 1.0 - cos;
+        Self::new([
+            [
+                t * x * x + cos,
+                t * x * y - sin * z,
+                t * x * z + sin * y,
+                0.0,
+            ],
+            [
+                t * x * y + sin * z,
+                t * y * y + cos,
+                t * y * z - sin * x,
+                0.0,
+            ],
+            [
+                t * x * z - sin * y,
+                t * y * z + sin * x,
+                t * z * z + cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn rows(&self) -> &[[f32; 4]; 4] {
+        &self.m
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Self::new(m)
+    }
+
+    /// Inverts the matrix by Gauss-Jordan elimination, `None` if it is
+    /// singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a = self.m.map(|row| row.map(f64::from));
+        let mut inv = Self::identity().m.map(|row| row.map(f64::from));
+
+        for column in 0..4 {
+            let pivot =
+                (column..4).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+            if a[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(column, pivot);
+            inv.swap(column, pivot);
+
+            let scale = 1.0 / a[column][column];
+            for j in 0..4 {
+                a[column][j] *= scale;
+                inv[column][j] *= scale;
+            }
+            for i in 0..4 {
+                if i == column {
+                    continue;
+                }
+                let factor = a[i][column];
+                for j in 0..4 {
+                    a[i][j] -= factor * a[column][j];
+                    inv[i][j] -= factor * inv[column][j];
+                }
+            }
+        }
+
+        Some(Self::new(inv.map(|row| row.map(|x| x as f32))))
+    }
+
+    /// Transforms a point, including the translation.
+    pub fn transform_point(&self, p: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3],
+            m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3],
+            m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3],
+        )
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Mul<Mat4> for Mat4 {
+    type Output = Mat4;
+
+    fn mul(self, rhs: Mat4) -> Mat4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Mat4::new(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat4::Mat4;
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+
+    fn assert_close(expected: Vec3, actual: Vec3) {
+        assert!(
+            (expected - actual).length() < 1e-5,
+            "expected {:?}, got {:?}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_mat4_translation_and_scaling() {
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        let t = Mat4::translation(Vec3::new(1.0, -1.0, 0.5));
+        assert_eq!(Vec3::new(2.0, 1.0, 3.5), t.transform_point(p));
+        assert_eq!(p, t.transform_vector(p));
+
+        let s = Mat4::scaling(Vec3::new(2.0, 3.0, -1.0));
+        assert_eq!(Vec3::new(2.0, 6.0, -3.0), s.transform_point(p));
+        // The scaling is applied first.
+        assert_eq!(Vec3::new(3.0, 5.0, -2.5), (t * s).transform_point(p));
+    }
+
+    #[test]
+    fn test_mat4_rotation() {
+        let r = Mat4::rotation(Vec3::new(0.0, 0.0, 2.0), PI / 2.0);
+        assert_close(
+            Vec3::new(0.0, 1.0, 0.0),
+            r.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+        );
+        assert_close(
+            Vec3::new(-1.0, 0.0, 0.0),
+            r.transform_vector(Vec3::new(0.0, 1.0, 0.0)),
+        );
+
+        let r = Mat4::rotation(Vec3::new(1.0, 1.0, 1.0), 2.0 * PI / 3.0);
+        assert_close(
+            Vec3::new(0.0, 1.0, 0.0),
+            r.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+        );
+    }
+
+    #[test]
+    fn test_mat4_inverse() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0))
+            * Mat4::rotation(Vec3::new(1.0, -2.0, 0.5), 0.7)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 3.0));
+        let inverse = m.inverse().unwrap();
+        let p = Vec3::new(-4.0, 0.5, 9.0);
+        assert_close(p, inverse.transform_point(m.transform_point(p)));
+        assert_close(p, m.transform_point(inverse.transform_point(p)));
+
+        assert_eq!(None, Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse());
+        assert_eq!(Some(Mat4::identity()), Mat4::identity().inverse());
+    }
+
+    #[test]
+    fn test_mat4_transpose() {
+        let m = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!([1.0, 2.0, 3.0, 1.0], m.transpose().rows()[3]);
+        assert_eq!(m, m.transpose().transpose());
+    }
+}
@@ -1,11 +1,55 @@
+use crate::textures::texture::Texture;
 use crate::vec3::Vec3;
+use std::sync::Arc;
 
-#[derive(Clone, Copy)]
+/// Parameters of the physically based metallic-roughness model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pbr {
+    /// 0 for dielectrics such as plastic or glass, 1 for metals, which tint
+    /// their reflection with the base color and have no diffuse part.
+    pub metallic: f32,
+    /// Perceptual roughness of the microfacets, from 0 for a perfect mirror
+    /// to 1 for a matte surface.
+    pub roughness: f32,
+    /// Share of the dielectric part that lets light through instead of
+    /// reflecting it diffusely, tinted by the base color.
+    pub transmission: f32,
+}
+
+impl Default for Pbr {
+    fn default() -> Self {
+        Self {
+            metallic: 0.0,
+            roughness: 0.5,
+            transmission: 0.0,
+        }
+    }
+}
+
+/// How a material reflects and transmits light.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Model {
+    /// The original model, a mix of Lambert diffuse, Phong specular, mirror
+    /// and refraction weighted by `Material::albedo`.
+    #[default]
+    Phong,
+    /// GGX microfacet reflection and transmission over a Lambert base that
+    /// only gets the light the microfacets do not reflect. The diffuse color
+    /// of the material is its base color.
+    Pbr(Pbr),
+}
+
+#[derive(Clone)]
 pub struct Material {
+    model: Model,
     refractive_index: f32,
     albedo: [f32; 4],
     diffuse_color: Vec3,
     specular_exponent: f32,
+    emission: Vec3,
+    absorption: Vec3,
+    texture: Option<Arc<dyn Texture + Sync + Send>>,
+    id: usize,
 }
 
 impl Material {
@@ -16,17 +60,35 @@ impl Material {
         specular_exponent: f32,
     ) -> Self {
         Self {
+            model: Model::Phong,
             refractive_index,
             albedo,
             diffuse_color,
             specular_exponent,
+            emission: Vec3::default(),
+            absorption: Vec3::default(),
+            texture: None,
+            id: 0,
         }
     }
 
+    /// A physically based material of the given base color.
+    pub fn pbr(base_color: Vec3, pbr: Pbr, refractive_index: f32) -> Self {
+        let mut material = Self::new(refractive_index, [0.0; 4], base_color, 0.0);
+        material.set_model(Model::Pbr(pbr));
+        material
+    }
+
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
     pub fn refractive_index(&self) -> f32 {
         self.refractive_index
     }
 
+    /// Weights of the Phong model: diffuse, specular highlight, mirror
+    /// reflection and refraction, in this order. Unused by other models.
     pub fn albedo(&self) -> &[f32; 4] {
         &self.albedo
     }
@@ -35,10 +97,49 @@ impl Material {
         self.diffuse_color
     }
 
+    /// Diffuse color at the surface coordinates `(u, v)` of the point `p`,
+    /// taken from the texture if there is one.
+    pub fn diffuse_color_at(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        match &self.texture {
+            Some(texture) => texture.value(u, v, p),
+            None => self.diffuse_color,
+        }
+    }
+
+    /// Beer–Lambert absorption coefficients per unit of distance travelled
+    /// inside the material, for colored glass.
+    pub fn absorption(&self) -> Vec3 {
+        self.absorption
+    }
+
+    /// Fraction of light that is left after `distance` inside the material.
+    pub fn transmittance(&self, distance: f32) -> Vec3 {
+        let a = self.absorption * -distance;
+        Vec3::new(a.x().exp(), a.y().exp(), a.z().exp())
+    }
+
+    pub fn texture(&self) -> Option<&Arc<dyn Texture + Sync + Send>> {
+        self.texture.as_ref()
+    }
+
     pub fn specular_exponent(&self) -> f32 {
         self.specular_exponent
     }
 
+    /// Radiance emitted by the surface itself.
+    pub fn emission(&self) -> Vec3 {
+        self.emission
+    }
+
+    /// Identifies the material in the material ID output, 0 if it has none.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn set_model(&mut self, model: Model) {
+        self.model = model;
+    }
+
     pub fn set_refractive_index(&mut self, refractive_index: f32) {
         self.refractive_index = refractive_index;
     }
@@ -54,15 +155,36 @@ impl Material {
     pub fn set_specular_exponent(&mut self, specular_exponent: f32) {
         self.specular_exponent = specular_exponent;
     }
+
+    pub fn set_emission(&mut self, emission: Vec3) {
+        self.emission = emission;
+    }
+
+    pub fn set_absorption(&mut self, absorption: Vec3) {
+        self.absorption = absorption;
+    }
+
+    pub fn set_texture(&mut self, texture: Option<Arc<dyn Texture + Sync + Send>>) {
+        self.texture = texture;
+    }
+
+    pub fn set_id(&mut self, id: usize) {
+        self.id = id;
+    }
 }
 
 impl Default for Material {
     fn default() -> Material {
         Material {
+            model: Model::Phong,
             refractive_index: 1.0,
             albedo: [2.0, 0.0, 0.0, 0.0],
             diffuse_color: Vec3::default(),
             specular_exponent: 0.0,
+            emission: Vec3::default(),
+            absorption: Vec3::default(),
+            texture: None,
+            id: 0,
         }
     }
 }
@@ -0,0 +1,119 @@
+use crate::vec3::Vec3;
+use std::f32::consts::PI;
+
+/// A homogeneous participating medium such as fog or smoke. Light passing
+/// through it is absorbed and scattered at rates per unit of distance, the
+/// scattered light leaves in a direction given by the Henyey-Greenstein
+/// phase function with asymmetry `g`: 0 scatters evenly in all directions,
+/// positive values mostly forward and negative ones back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Medium {
+    absorption: Vec3,
+    scattering: Vec3,
+    g: f32,
+}
+
+impl Medium {
+    pub fn new(absorption: Vec3, scattering: Vec3, g: f32) -> Self {
+        Self {
+            absorption,
+            scattering,
+            g,
+        }
+    }
+
+    /// A medium that takes `density` of the light per unit of distance, of
+    /// which the share `color` is scattered and the rest absorbed.
+    pub fn with_density(density: f32, color: Vec3) -> Self {
+        Self::new(
+            (Vec3::new(1.0, 1.0, 1.0) - color) * density,
+            color * density,
+            0.0,
+        )
+    }
+
+    pub fn absorption(&self) -> Vec3 {
+        self.absorption
+    }
+
+    pub fn scattering(&self) -> Vec3 {
+        self.scattering
+    }
+
+    pub fn g(&self) -> f32 {
+        self.g
+    }
+
+    /// Rate at which light is taken out of a ray, by absorption or by
+    /// scattering into other directions.
+    pub fn extinction(&self) -> Vec3 {
+        self.absorption + self.scattering
+    }
+
+    pub fn set_absorption(&mut self, absorption: Vec3) {
+        self.absorption = absorption;
+    }
+
+    pub fn set_scattering(&mut self, scattering: Vec3) {
+        self.scattering = scattering;
+    }
+
+    pub fn set_g(&mut self, g: f32) {
+        self.g = g;
+    }
+
+    /// Fraction of light that is left after `distance` in the medium.
+    pub fn transmittance(&self, distance: f32) -> Vec3 {
+        let a = self.extinction() * -distance;
+        Vec3::new(a.x().exp(), a.y().exp(), a.z().exp())
+    }
+
+    /// Density over solid angle of scattering by an angle with the cosine
+    /// `cos` from the direction of travel.
+    pub fn phase(&self, cos: f32) -> f32 {
+        henyey_greenstein(cos, self.g)
+    }
+}
+
+/// The Henyey-Greenstein phase function for the cosine of the scattering
+/// angle and asymmetry `g`, normalized over the sphere.
+pub fn henyey_greenstein(cos: f32, g: f32) -> f32 {
+    let denominator = 1.0 + g * g - 2.0 * g * cos;
+    (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::media::medium::{henyey_greenstein, Medium};
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_henyey_greenstein() {
+        // Integrates to 1 over the sphere, with a mean cosine of g.
+        for g in [-0.6, 0.0, 0.3, 0.8] {
+            let steps = 20_000;
+            let (mut total, mut mean_cos) = (0.0, 0.0);
+            for i in 0..steps {
+                let cos = -1.0 + 2.0 * (i as f32 + 0.5) / steps as f32;
+                let weight = henyey_greenstein(cos, g) * 2.0 * PI * 2.0 / steps as f32;
+                total += weight;
+                mean_cos += cos * weight;
+            }
+            assert!((total - 1.0).abs() < 1e-3, "{}", total);
+            assert!((mean_cos - g).abs() < 1e-3, "{}", mean_cos);
+        }
+        assert!(henyey_greenstein(1.0, 0.5) > henyey_greenstein(-1.0, 0.5));
+    }
+
+    #[test]
+    fn test_medium() {
+        let medium = Medium::with_density(2.0, Vec3::new(1.0, 0.5, 0.0));
+        assert_eq!(Vec3::new(0.0, 1.0, 2.0), medium.absorption());
+        assert_eq!(Vec3::new(2.0, 1.0, 0.0), medium.scattering());
+        let transmittance = medium.transmittance(0.5);
+        assert!((transmittance.x() - (-1.0f32).exp()).abs() < 1e-6);
+        assert_eq!(transmittance.x(), transmittance.z());
+        assert_eq!(1.0 / (4.0 * PI), medium.phase(0.3));
+    }
+}
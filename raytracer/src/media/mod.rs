@@ -0,0 +1,2 @@
+pub mod medium;
+pub mod volume;
@@ -0,0 +1,80 @@
+use crate::media::medium::Medium;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::Object;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// A medium that fills a closed object, e.g. smoke in a glass. The surface
+/// of the object only bounds the medium, rays pass it unchanged.
+#[derive(Clone)]
+pub struct Volume {
+    boundary: Arc<dyn Object + Sync + Send>,
+    medium: Medium,
+    bounds: Aabb,
+}
+
+impl Volume {
+    pub fn new(boundary: Arc<dyn Object + Sync + Send>, medium: Medium) -> Self {
+        Self {
+            bounds: boundary.bounds(),
+            boundary,
+            medium,
+        }
+    }
+
+    pub fn boundary(&self) -> &Arc<dyn Object + Sync + Send> {
+        &self.boundary
+    }
+
+    pub fn medium(&self) -> &Medium {
+        &self.medium
+    }
+
+    /// Sorted spans of the ray between its origin and `t_max` that lie in the
+    /// medium.
+    pub fn spans(&self, ray: &Ray, t_max: f32) -> Vec<(f32, f32)> {
+        let dir = ray.dir();
+        let inv_dir = Vec3::new(1.0 / dir.x(), 1.0 / dir.y(), 1.0 / dir.z());
+        if self.bounds.hit(ray.orig(), inv_dir, t_max).is_none() {
+            return Vec::new();
+        }
+        self.boundary
+            .intervals(ray)
+            .into_iter()
+            .map(|(start, end)| (start.max(0.0), end.min(t_max)))
+            .filter(|(start, end)| start < end)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::media::medium::Medium;
+    use crate::media::volume::Volume;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::vec3::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_volume_spans() {
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, -5.0), 1.0, Material::default());
+        let volume = Volume::new(Arc::new(sphere), Medium::with_density(1.0, Vec3::default()));
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0));
+
+        let spans = volume.spans(&ray, f32::INFINITY);
+        assert_eq!(1, spans.len());
+        assert!((spans[0].0 - 4.0).abs() < 1e-4 && (spans[0].1 - 6.0).abs() < 1e-4);
+        // Cut off by a surface in the medium, or ending before it.
+        assert!((volume.spans(&ray, 5.0)[0].1 - 5.0).abs() < 1e-6);
+        assert!(volume.spans(&ray, 3.0).is_empty());
+
+        // From the inside the medium starts at the origin.
+        let inside = Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(0.0, volume.spans(&inside, f32::INFINITY)[0].0);
+        let away = Ray::new(Vec3::default(), Vec3::new(0.0, 1.0, 0.0));
+        assert!(volume.spans(&away, f32::INFINITY).is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::vec3::Vec3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    min: Vec3,
+    max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min: min.min(max),
+            max: min.max(max),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    /// Bounds of an object that extends to infinity along every axis.
+    pub fn infinite() -> Self {
+        Self {
+            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    /// Bounds of a disk around `center` facing the unit vector `normal`.
+    pub fn disk(center: Vec3, normal: Vec3, radius: f32) -> Self {
+        let extent = |n: f32| radius * (1.0 - n * n).max(0.0).sqrt();
+        let half = Vec3::new(extent(normal.x()), extent(normal.y()), extent(normal.z()));
+        Self::new(center - half, center + half)
+    }
+
+    pub fn min(&self) -> Vec3 {
+        self.min
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.max
+    }
+
+    pub fn is_finite(&self) -> bool {
+        (0..3).all(|axis| self.min[axis].is_finite() && self.max[axis].is_finite())
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn union(&self, other: &Aabb) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    /// Overlap of the two boxes, empty if they do not overlap.
+    pub fn intersection(&self, other: &Aabb) -> Self {
+        let min = self.min.max(other.min);
+        let max = self.max.min(other.max);
+        if (0..3).any(|axis| min[axis] > max[axis]) {
+            return Self::empty();
+        }
+        Self { min, max }
+    }
+
+    /// The box moved by `offset`.
+    pub fn translate(&self, offset: Vec3) -> Self {
+        Self {
+            min: self.min + offset,
+            max: self.max + offset,
+        }
+    }
+
+    pub fn grow(&self, p: Vec3) -> Self {
+        Self {
+            min: self.min.min(p),
+            max: self.max.max(p),
+        }
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let d = self.max - self.min;
+        if d.x() < 0.0 || d.y() < 0.0 || d.z() < 0.0 {
+            return 0.0;
+        }
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    pub fn largest_axis(&self) -> usize {
+        let d = self.max - self.min;
+        if d.x() >= d.y() && d.x() >= d.z() {
+            0
+        } else if d.y() >= d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Returns the distance at which the ray enters the box if it does so
+    /// before `t_max`. `inv_dir` is the componentwise reciprocal of the ray
+    /// direction.
+    pub fn hit(&self, orig: Vec3, inv_dir: Vec3, t_max: f32) -> Option<f32> {
+        let mut t_near = 0.0_f32;
+        let mut t_far = t_max;
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - orig[axis]) * inv_dir[axis];
+            let t1 = (self.max[axis] - orig[axis]) * inv_dir[axis];
+            let (t0, t1) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+            // The far bound is widened so that rounding never culls a grazing hit.
+            t_near = t_near.max(t0);
+            t_far = t_far.min(t1 * (1.0 + 4.0 * f32::EPSILON));
+            if t_near > t_far {
+                return None;
+            }
+        }
+        Some(t_near)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::aabb::Aabb;
+    use crate::vec3::Vec3;
+
+    fn inv(dir: Vec3) -> Vec3 {
+        Vec3::new(1.0 / dir.x(), 1.0 / dir.y(), 1.0 / dir.z())
+    }
+
+    #[test]
+    fn test_aabb_hit() {
+        let aabb = Aabb::new(Vec3::new(-1.0, -1.0, -1.0), Vec3::new(1.0, 1.0, 1.0));
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(
+            Some(4.0),
+            aabb.hit(Vec3::new(0.0, 0.0, 5.0), inv(dir), f32::MAX)
+        );
+        assert_eq!(None, aabb.hit(Vec3::new(0.0, 0.0, 5.0), inv(dir), 3.0));
+        assert_eq!(None, aabb.hit(Vec3::new(2.0, 0.0, 5.0), inv(dir), f32::MAX));
+        assert_eq!(
+            None,
+            aabb.hit(Vec3::new(0.0, 0.0, -5.0), inv(dir), f32::MAX)
+        );
+        assert_eq!(Some(0.0), aabb.hit(Vec3::default(), inv(dir), f32::MAX));
+    }
+
+    #[test]
+    fn test_aabb_union() {
+        let a = Aabb::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0));
+        let b = Aabb::new(Vec3::new(-1.0, 2.0, 0.5), Vec3::new(0.0, 3.0, 0.5));
+        let u = a.union(&b);
+        assert_eq!(Vec3::new(-1.0, 0.0, 0.0), u.min());
+        assert_eq!(Vec3::new(1.0, 3.0, 1.0), u.max());
+        assert_eq!(a, Aabb::empty().union(&a));
+        assert_eq!(0.0, Aabb::empty().surface_area());
+        assert_eq!(6.0, a.surface_area());
+
+        let overlap = a.intersection(&Aabb::new(
+            Vec3::new(0.5, -1.0, 0.5),
+            Vec3::new(2.0, 0.5, 2.0),
+        ));
+        assert_eq!(Vec3::new(0.5, 0.0, 0.5), overlap.min());
+        assert_eq!(Vec3::new(1.0, 0.5, 1.0), overlap.max());
+        assert_eq!(Aabb::empty(), a.intersection(&b));
+
+        let moved = a.translate(Vec3::new(1.0, 0.0, -1.0));
+        assert_eq!(Vec3::new(1.0, 0.0, -1.0), moved.min());
+        assert_eq!(Vec3::new(2.0, 1.0, 0.0), moved.max());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::frame::{azimuth, Frame};
+use crate::objects::object::{Hit, Object};
+use crate::objects::roots::quadratic;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A cone from a circular base, closed by a flat cap, up to its apex.
+#[derive(Clone)]
+pub struct Cone {
+    frame: Frame,
+    height: f32,
+    radius: f32,
+    material: Material,
+}
+
+impl Cone {
+    pub fn new(base: Vec3, apex: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            frame: Frame::new(base, apex - base),
+            height: (apex - base).length(),
+            radius,
+            material,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    // Radius shrinkage per unit of height.
+    fn slope(&self) -> f32 {
+        self.radius / self.height
+    }
+
+    // Whether the local point `p` is closer to the base cap than to the side.
+    fn on_cap(&self, p: Vec3) -> bool {
+        let k = self.slope();
+        let rho = (p.x().powi(2) + p.y().powi(2)).sqrt();
+        let side = (rho - k * (self.height - p.z())).abs() / (1.0 + k * k).sqrt();
+        p.z().abs() < side
+    }
+
+    fn distance(&self, orig: Vec3, dir: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let o = self.frame.to_local(orig);
+        let d = self.frame.vector_to_local(dir);
+        let k2 = self.slope().powi(2);
+        // Distance below the apex along the axis.
+        let h = self.height - o.z();
+        let mut nearest = f32::INFINITY;
+
+        if let Some((t0, t1)) = quadratic(
+            d.x().powi(2) + d.y().powi(2) - k2 * d.z().powi(2),
+            2.0 * (o.x() * d.x() + o.y() * d.y() + k2 * h * d.z()),
+            o.x().powi(2) + o.y().powi(2) - k2 * h * h,
+        ) {
+            for t in [t0, t1] {
+                // The equation also describes the mirrored cone above the apex.
+                let z = o.z() + t * d.z();
+                if t > t_min && t <= t_max && (0.0..=self.height).contains(&z) {
+                    nearest = nearest.min(t);
+                }
+            }
+        }
+        if d.z() != 0.0 {
+            let t = -o.z() / d.z();
+            let p = o + d * t;
+            if t > t_min && t <= t_max && p.x().powi(2) + p.y().powi(2) <= self.radius.powi(2) {
+                nearest = nearest.min(t);
+            }
+        }
+
+        nearest.is_finite().then_some(nearest)
+    }
+
+    fn norm(&self, p: Vec3) -> Vec3 {
+        let local = self.frame.to_local(p);
+        if self.on_cap(local) {
+            return -self.frame.axis();
+        }
+        let rho = (local.x().powi(2) + local.y().powi(2)).sqrt();
+        self.frame
+            .vector_to_world(Vec3::new(local.x(), local.y(), self.slope() * rho))
+            .norm()
+    }
+
+    // Same layout as the cylinder: `u` around the axis and `v` from the base
+    // to the apex on the side, the unit square on the cap.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = self.frame.to_local(p);
+        if self.on_cap(local) {
+            return (
+                (local.x() / self.radius + 1.0) * 0.5,
+                (local.y() / self.radius + 1.0) * 0.5,
+            );
+        }
+        (azimuth(local), local.z() / self.height)
+    }
+}
+
+impl Object for Cone {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let t = self.distance(ray.orig(), ray.dir(), t_min, t_max)?;
+        let point = ray.point(t);
+        Some(Hit::new(
+            ray.dir(),
+            t,
+            point,
+            self.norm(point),
+            self.uv(point),
+            &self.material,
+        ))
+    }
+
+    // Centroid of the solid cone.
+    fn center(&self) -> Vec3 {
+        self.frame.origin() + self.frame.axis() * (self.height * 0.25)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let axis = self.frame.axis();
+        let base = self.frame.origin();
+        Aabb::disk(base, axis, self.radius).grow(base + axis * self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::cone::Cone;
+    use crate::objects::object::Object;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    fn cone() -> Cone {
+        Cone::new(
+            Vec3::new(0.0, -1.0, -5.0),
+            Vec3::new(0.0, 1.0, -5.0),
+            1.0,
+            Material::default(),
+        )
+    }
+
+    #[test]
+    fn test_cone_side() {
+        let cone = cone();
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        // Halfway up the radius is 0.5.
+        let t = cone
+            .distance(Vec3::default(), dir, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 4.5).abs() < 1e-5);
+        assert!(cone
+            .distance(Vec3::new(0.6, 0.0, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        // The mirrored cone above the apex is not part of the object.
+        assert!(cone
+            .distance(Vec3::new(0.0, 1.5, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+
+        let n = cone.norm(Vec3::new(0.0, 0.0, -4.5));
+        assert!((n - Vec3::new(0.0, 1.0, 2.0).norm()).length() < 1e-5);
+        let (_, v) = cone.uv(Vec3::new(0.0, 0.0, -4.5));
+        assert!((v - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_cone_cap() {
+        let cone = cone();
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let t = cone
+            .distance(Vec3::new(0.5, -5.0, -5.0), up, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 4.0).abs() < 1e-5);
+        assert!(cone
+            .distance(Vec3::new(1.5, -5.0, -5.0), up, EPS, f32::INFINITY)
+            .is_none());
+
+        let n = cone.norm(Vec3::new(0.5, -1.0, -5.0));
+        assert!((n - Vec3::new(0.0, -1.0, 0.0)).length() < 1e-5);
+        let bounds = cone.bounds();
+        assert!((bounds.min() - Vec3::new(-1.0, -1.0, -6.0)).length() < 1e-5);
+        assert!((bounds.max() - Vec3::new(1.0, 1.0, -4.0)).length() < 1e-5);
+    }
+}
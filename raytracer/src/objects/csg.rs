@@ -0,0 +1,357 @@
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Boolean operation of a `Csg` node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOp {
+    Union,
+    Intersection,
+    /// The left object with the right one cut out of it.
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(self, left: bool, right: bool) -> bool {
+        match self {
+            CsgOp::Union => left || right,
+            CsgOp::Intersection => left && right,
+            CsgOp::Difference => left && !right,
+        }
+    }
+}
+
+impl FromStr for CsgOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "union" => Ok(CsgOp::Union),
+            "intersection" => Ok(CsgOp::Intersection),
+            "difference" => Ok(CsgOp::Difference),
+            _ => Err(format!("unknown CSG operation `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for CsgOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsgOp::Union => write!(f, "union"),
+            CsgOp::Intersection => write!(f, "intersection"),
+            CsgOp::Difference => write!(f, "difference"),
+        }
+    }
+}
+
+/// Constructive solid geometry: two closed objects combined by a boolean
+/// operation on the spans of a ray inside each of them. The surface keeps
+/// the normal and material of the child it belongs to, the normal of a cut
+/// made by `Difference` points into the removed object.
+pub struct Csg {
+    op: CsgOp,
+    left: Arc<dyn Object + Sync + Send>,
+    right: Arc<dyn Object + Sync + Send>,
+    bounds: Aabb,
+}
+
+impl Csg {
+    pub fn new(
+        op: CsgOp,
+        left: Arc<dyn Object + Sync + Send>,
+        right: Arc<dyn Object + Sync + Send>,
+    ) -> Self {
+        let bounds = match op {
+            CsgOp::Union => left.bounds().union(&right.bounds()),
+            CsgOp::Intersection => left.bounds().intersection(&right.bounds()),
+            CsgOp::Difference => left.bounds(),
+        };
+        Self {
+            op,
+            left,
+            right,
+            bounds,
+        }
+    }
+
+    pub fn op(&self) -> CsgOp {
+        self.op
+    }
+
+    pub fn left(&self) -> &Arc<dyn Object + Sync + Send> {
+        &self.left
+    }
+
+    pub fn right(&self) -> &Arc<dyn Object + Sync + Send> {
+        &self.right
+    }
+
+    // Every boundary of the combined object along the ray in order, with
+    // whether it belongs to the right child. Entries and exits alternate,
+    // starting with an entry.
+    fn boundaries(&self, ray: &Ray) -> Vec<(f32, bool)> {
+        let mut events: Vec<(f32, bool)> = Vec::new();
+        let left = self.left.intervals(ray);
+        let right = self.right.intervals(ray);
+        for (spans, is_right) in [(left, false), (right, true)] {
+            for (entry, exit) in spans {
+                events.push((entry, is_right));
+                events.push((exit, is_right));
+            }
+        }
+        events.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Sweeps over the entries and exits of both children and keeps the
+        // places where the operation starts or stops to hold.
+        let mut boundaries = Vec::new();
+        let (mut in_left, mut in_right) = (false, false);
+        let mut inside = false;
+        let mut i = 0;
+        while i < events.len() {
+            // Coinciding boundaries are crossed together, so that touching
+            // spans do not leave empty ones behind.
+            let t = events[i].0;
+            let mut source = false;
+            while i < events.len() && events[i].0 == t {
+                source = events[i].1;
+                if source {
+                    in_right = !in_right;
+                } else {
+                    in_left = !in_left;
+                }
+                i += 1;
+            }
+            if self.op.inside(in_left, in_right) != inside {
+                inside = !inside;
+                boundaries.push((t, source));
+            }
+        }
+        boundaries
+    }
+}
+
+impl Object for Csg {
+    // The first boundary in range is looked up again on the child it belongs
+    // to, which provides the normal, surface coordinates and material.
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        for (t, is_right) in self.boundaries(ray) {
+            if t <= t_min || !t.is_finite() {
+                continue;
+            }
+            if t > t_max {
+                break;
+            }
+            let child = if is_right { &self.right } else { &self.left };
+            let tolerance = 1e-4 * t.max(1.0);
+            if let Some(mut hit) = child.intersect(ray, t - tolerance, t + tolerance) {
+                if is_right && self.op == CsgOp::Difference {
+                    hit.normal = -hit.normal;
+                    hit.shading_normal = -hit.shading_normal;
+                    hit.front_face = !hit.front_face;
+                }
+                return Some(hit);
+            }
+        }
+        None
+    }
+
+    fn center(&self) -> Vec3 {
+        match self.op {
+            CsgOp::Union => self.bounds.centroid(),
+            CsgOp::Intersection | CsgOp::Difference => self.left.center(),
+        }
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<(f32, f32)> {
+        self.boundaries(ray)
+            .chunks(2)
+            .map(|pair| (pair[0].0, pair.get(1).map_or(f32::INFINITY, |exit| exit.0)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::csg::{Csg, CsgOp};
+    use crate::objects::object::Object;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::sync::Arc;
+
+    // Two unit spheres at x = -0.5 and x = 0.5, red and blue.
+    fn csg(op: CsgOp) -> Csg {
+        let mut red = Material::default();
+        red.set_diffuse_color(Vec3::new(1.0, 0.0, 0.0));
+        let mut blue = Material::default();
+        blue.set_diffuse_color(Vec3::new(0.0, 0.0, 1.0));
+        Csg::new(
+            op,
+            Arc::new(Sphere::new(Vec3::new(-0.5, 0.0, -5.0), 1.0, red)),
+            Arc::new(Sphere::new(Vec3::new(0.5, 0.0, -5.0), 1.0, blue)),
+        )
+    }
+
+    fn distance(csg: &Csg, orig: Vec3) -> Option<f32> {
+        csg.intersect(
+            &Ray::new(orig, Vec3::new(0.0, 0.0, -1.0)),
+            EPS,
+            f32::INFINITY,
+        )
+        .map(|hit| hit.t)
+    }
+
+    fn assert_close(expected: f32, d: Option<f32>) {
+        assert!(
+            d.is_some_and(|d| (d - expected).abs() < 1e-4),
+            "expected {}, got {:?}",
+            expected,
+            d
+        );
+    }
+
+    #[test]
+    fn test_csg_intervals() {
+        let along_x = |op| {
+            csg(op).intervals(&Ray::new(
+                Vec3::new(-5.0, 0.0, -5.0),
+                Vec3::new(1.0, 0.0, 0.0),
+            ))
+        };
+        let close = |expected: &[(f32, f32)], actual: Vec<(f32, f32)>| {
+            assert_eq!(expected.len(), actual.len(), "{:?}", actual);
+            for (e, a) in expected.iter().zip(&actual) {
+                assert!(
+                    (e.0 - a.0).abs() < 1e-4 && (e.1 - a.1).abs() < 1e-4,
+                    "{:?}",
+                    actual
+                );
+            }
+        };
+        close(&[(3.5, 6.5)], along_x(CsgOp::Union));
+        close(&[(4.5, 5.5)], along_x(CsgOp::Intersection));
+        close(&[(3.5, 4.5)], along_x(CsgOp::Difference));
+
+        // Starting inside both spheres.
+        let inside = csg(CsgOp::Intersection).intervals(&Ray::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ));
+        assert_eq!(1, inside.len());
+        assert_eq!(f32::NEG_INFINITY, inside[0].0);
+        assert!((inside[0].1 - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_csg_intersect() {
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let lens = csg(CsgOp::Intersection);
+        // Through the lens the far side of the right sphere is hit first.
+        assert_close(5.0 - 0.75f32.sqrt(), distance(&lens, Vec3::default()));
+        assert!(distance(&lens, Vec3::new(-1.0, 0.0, 0.0)).is_none());
+        // The range applies to the combined surface, not to the children.
+        assert!(lens
+            .intersect(&Ray::new(Vec3::default(), forward), EPS, 4.0)
+            .is_none());
+
+        let cut = csg(CsgOp::Difference);
+        assert!(distance(&cut, Vec3::new(1.0, 0.0, 0.0)).is_none());
+        assert_close(4.0, distance(&cut, Vec3::new(-0.5, 0.0, 0.0)));
+        // Along x = 0 both spheres cover the same span, nothing is left.
+        assert!(distance(&cut, Vec3::default()).is_none());
+        // Along x = -0.2 the blue sphere carves the middle out of the red one.
+        let spans = cut.intervals(&Ray::new(Vec3::new(-0.2, 0.0, 0.0), forward));
+        assert_eq!(2, spans.len());
+        assert!((spans[0].1 - (5.0 - 0.51f32.sqrt())).abs() < 1e-4);
+        // Starting inside the remaining red part, its exit is hit next.
+        let exit = cut
+            .intersect(
+                &Ray::new(Vec3::new(-0.2, 0.0, -4.2), forward),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((exit.t - (0.8 - 0.51f32.sqrt())).abs() < 1e-4);
+        assert!(!exit.front_face);
+
+        let union = csg(CsgOp::Union);
+        assert_close(4.0, distance(&union, Vec3::new(0.5, 0.0, 0.0)));
+        assert!(distance(&union, Vec3::new(2.0, 0.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_csg_surface() {
+        let red = Vec3::new(1.0, 0.0, 0.0);
+        let blue = Vec3::new(0.0, 0.0, 1.0);
+        let along_x = |csg: &Csg, x: f32| {
+            let (orig, dir) = if x < 0.0 {
+                (Vec3::new(-5.0, 0.0, -5.0), Vec3::new(1.0, 0.0, 0.0))
+            } else {
+                (Vec3::new(5.0, 0.0, -5.0), Vec3::new(-1.0, 0.0, 0.0))
+            };
+            let hit = csg
+                .intersect(&Ray::new(orig, dir), EPS, f32::INFINITY)
+                .unwrap();
+            assert!((hit.point.x() - x).abs() < 1e-4, "{:?}", hit.point);
+            (hit.normal, hit.material.diffuse_color())
+        };
+
+        // The cut is a piece of the blue sphere seen from inside.
+        let cut = csg(CsgOp::Difference);
+        let (normal, color) = along_x(&cut, -1.5);
+        assert!((normal - Vec3::new(-1.0, 0.0, 0.0)).length() < 1e-5);
+        assert_eq!(red, color);
+        let hit = cut
+            .intersect(
+                &Ray::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(-1.0, 0.0, 0.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 0.5).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-5);
+        assert!(hit.front_face);
+        assert_eq!(blue, hit.material.diffuse_color());
+
+        // The left face of the lens belongs to the blue sphere.
+        let lens = csg(CsgOp::Intersection);
+        assert_eq!(blue, along_x(&lens, -0.5).1);
+        assert_eq!(red, along_x(&lens, 0.5).1);
+
+        let bounds = lens.bounds();
+        assert_eq!(Vec3::new(-0.5, -1.0, -6.0), bounds.min());
+        assert_eq!(Vec3::new(0.5, 1.0, -4.0), bounds.max());
+    }
+
+    #[test]
+    fn test_csg_nested() {
+        // Another sphere cut out of the lens.
+        let hole = Sphere::new(Vec3::new(0.0, 0.0, -4.0), 0.5, Material::default());
+        let nested = Csg::new(
+            CsgOp::Difference,
+            Arc::new(csg(CsgOp::Intersection)),
+            Arc::new(hole),
+        );
+        let hit = nested
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 4.5).abs() < 1e-4);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-4);
+        assert_eq!("difference", CsgOp::Difference.to_string());
+        assert_eq!(Ok(CsgOp::Union), "union".parse());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::mat4::Mat4;
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A box given by its center, half extents and three orthonormal axes.
+#[derive(Clone)]
+pub struct Cuboid {
+    center: Vec3,
+    half_size: Vec3,
+    axes: [Vec3; 3],
+    material: Material,
+}
+
+impl Cuboid {
+    /// Axis-aligned box between the corners `min` and `max`.
+    pub fn new(min: Vec3, max: Vec3, material: Material) -> Self {
+        let (min, max) = (min.min(max), min.max(max));
+        Self {
+            center: (min + max) * 0.5,
+            half_size: (max - min) * 0.5,
+            axes: [
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+            ],
+            material,
+        }
+    }
+
+    /// Box of the given `size` around `center`, turned by `rotation`. Any
+    /// translation or scaling in `rotation` is ignored.
+    pub fn oriented(center: Vec3, size: Vec3, rotation: &Mat4, material: Material) -> Self {
+        let axis = |x, y, z| rotation.transform_vector(Vec3::new(x, y, z)).norm();
+        Self {
+            center,
+            half_size: Vec3::new(size.x().abs(), size.y().abs(), size.z().abs()) * 0.5,
+            axes: [
+                axis(1.0, 0.0, 0.0),
+                axis(0.0, 1.0, 0.0),
+                axis(0.0, 0.0, 1.0),
+            ],
+            material,
+        }
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.half_size * 2.0
+    }
+
+    pub fn axes(&self) -> &[Vec3; 3] {
+        &self.axes
+    }
+
+    fn to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(v * self.axes[0], v * self.axes[1], v * self.axes[2])
+    }
+
+    // Axis of the face the local point `p` lies on.
+    fn face(&self, p: Vec3) -> usize {
+        let distance = |i: usize| p[i].abs() / self.half_size[i];
+        (0..3)
+            .max_by(|&i, &j| distance(i).total_cmp(&distance(j)))
+            .unwrap_or(0)
+    }
+
+    fn distance(&self, orig: Vec3, dir: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let o = self.to_local(orig - self.center);
+        let d = self.to_local(dir);
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        for i in 0..3 {
+            if d[i] == 0.0 {
+                if o[i].abs() > self.half_size[i] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (-self.half_size[i] - o[i]) / d[i];
+            let t1 = (self.half_size[i] - o[i]) / d[i];
+            t_near = t_near.max(t0.min(t1));
+            t_far = t_far.min(t0.max(t1));
+        }
+        if t_near > t_far {
+            return None;
+        }
+        [t_near, t_far]
+            .into_iter()
+            .find(|&t| t > t_min && t <= t_max)
+    }
+
+    fn norm(&self, p: Vec3) -> Vec3 {
+        let local = self.to_local(p - self.center);
+        let face = self.face(local);
+        self.axes[face] * local[face].signum()
+    }
+
+    // Every face spans the whole `[0, 1]` square along the next two axes.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = self.to_local(p - self.center);
+        let face = self.face(local);
+        let coordinate = |i: usize| {
+            let i = (face + i) % 3;
+            (local[i] / self.half_size[i] + 1.0) * 0.5
+        };
+        (coordinate(1), coordinate(2))
+    }
+}
+
+impl Object for Cuboid {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let t = self.distance(ray.orig(), ray.dir(), t_min, t_max)?;
+        let point = ray.point(t);
+        Some(Hit::new(
+            ray.dir(),
+            t,
+            point,
+            self.norm(point),
+            self.uv(point),
+            &self.material,
+        ))
+    }
+
+    fn center(&self) -> Vec3 {
+        self.center
+    }
+
+    fn bounds(&self) -> Aabb {
+        let extent = |i: usize| {
+            (0..3)
+                .map(|j| self.axes[j][i].abs() * self.half_size[j])
+                .sum::<f32>()
+        };
+        let half = Vec3::new(extent(0), extent(1), extent(2));
+        Aabb::new(self.center - half, self.center + half)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat4::Mat4;
+    use crate::material::Material;
+    use crate::objects::cuboid::Cuboid;
+    use crate::objects::object::Object;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+
+    #[test]
+    fn test_cuboid_intersect() {
+        let cuboid = Cuboid::new(
+            Vec3::new(-1.0, -2.0, -1.0),
+            Vec3::new(1.0, 2.0, 1.0),
+            Material::default(),
+        );
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(
+            Some(4.0),
+            cuboid.distance(Vec3::new(0.5, 1.5, 5.0), dir, EPS, f32::INFINITY)
+        );
+        assert!(cuboid
+            .distance(Vec3::new(1.5, 0.0, 5.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        assert!(cuboid
+            .distance(Vec3::new(0.0, 0.0, -5.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        // From the inside the far face is hit.
+        assert_eq!(
+            Some(1.0),
+            cuboid.distance(Vec3::default(), dir, EPS, f32::INFINITY)
+        );
+
+        assert_eq!(
+            Vec3::new(0.0, 0.0, 1.0),
+            cuboid.norm(Vec3::new(0.5, 1.5, 1.0))
+        );
+        assert_eq!(
+            Vec3::new(0.0, -1.0, 0.0),
+            cuboid.norm(Vec3::new(0.9, -2.0, 0.0))
+        );
+        assert_eq!((0.5, 0.75), cuboid.uv(Vec3::new(0.0, 1.0, 1.0)));
+    }
+
+    #[test]
+    fn test_cuboid_oriented() {
+        let rotation = Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), PI / 4.0);
+        let cuboid = Cuboid::oriented(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(2.0, 2.0, 2.0),
+            &rotation,
+            Material::default(),
+        );
+        // The edge of the rotated cube points at the viewer.
+        let t = cuboid
+            .distance(
+                Vec3::default(),
+                Vec3::new(0.0, 0.0, -1.0),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((t - (5.0 - 2.0_f32.sqrt())).abs() < 1e-5);
+        assert!(cuboid
+            .distance(
+                Vec3::new(1.5, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, -1.0),
+                EPS,
+                f32::INFINITY
+            )
+            .is_none());
+
+        let n = cuboid.norm(Vec3::new(0.5, 0.0, -5.5));
+        assert!((n - Vec3::new(1.0, 0.0, -1.0).norm()).length() < 1e-5);
+        let bounds = cuboid.bounds();
+        assert!((bounds.max().x() - 2.0_f32.sqrt()).abs() < 1e-5);
+        assert!((bounds.max().y() - 1.0).abs() < 1e-5);
+    }
+}
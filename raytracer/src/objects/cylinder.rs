@@ -0,0 +1,193 @@
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::frame::{azimuth, Frame};
+use crate::objects::object::{Hit, Object};
+use crate::objects::roots::quadratic;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A cylinder between two points, closed by flat caps.
+#[derive(Clone)]
+pub struct Cylinder {
+    frame: Frame,
+    height: f32,
+    radius: f32,
+    material: Material,
+}
+
+impl Cylinder {
+    pub fn new(base: Vec3, top: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            frame: Frame::new(base, top - base),
+            height: (top - base).length(),
+            radius,
+            material,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        self.height
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    // Whether the local point `p` is closer to a cap than to the side,
+    // and which one.
+    fn cap(&self, p: Vec3) -> Option<f32> {
+        let side = ((p.x().powi(2) + p.y().powi(2)).sqrt() - self.radius).abs();
+        let (cap, distance) = if p.z() < self.height * 0.5 {
+            (-1.0, p.z().abs())
+        } else {
+            (1.0, (p.z() - self.height).abs())
+        };
+        (distance < side).then_some(cap)
+    }
+
+    fn distance(&self, orig: Vec3, dir: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let o = self.frame.to_local(orig);
+        let d = self.frame.vector_to_local(dir);
+        let mut nearest = f32::INFINITY;
+
+        if let Some((t0, t1)) = quadratic(
+            d.x().powi(2) + d.y().powi(2),
+            2.0 * (o.x() * d.x() + o.y() * d.y()),
+            o.x().powi(2) + o.y().powi(2) - self.radius.powi(2),
+        ) {
+            for t in [t0, t1] {
+                let z = o.z() + t * d.z();
+                if t > t_min && t <= t_max && (0.0..=self.height).contains(&z) {
+                    nearest = nearest.min(t);
+                }
+            }
+        }
+        if d.z() != 0.0 {
+            for z in [0.0, self.height] {
+                let t = (z - o.z()) / d.z();
+                let p = o + d * t;
+                if t > t_min && t <= t_max && p.x().powi(2) + p.y().powi(2) <= self.radius.powi(2) {
+                    nearest = nearest.min(t);
+                }
+            }
+        }
+
+        nearest.is_finite().then_some(nearest)
+    }
+
+    fn norm(&self, p: Vec3) -> Vec3 {
+        let local = self.frame.to_local(p);
+        match self.cap(local) {
+            Some(cap) => self.frame.axis() * cap,
+            None => self
+                .frame
+                .vector_to_world(Vec3::new(local.x(), local.y(), 0.0))
+                .norm(),
+        }
+    }
+
+    // The side wraps around `u` with `v` running from the base to the top,
+    // the caps are mapped onto the unit square.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = self.frame.to_local(p);
+        match self.cap(local) {
+            Some(_) => (
+                (local.x() / self.radius + 1.0) * 0.5,
+                (local.y() / self.radius + 1.0) * 0.5,
+            ),
+            None => (azimuth(local), local.z() / self.height),
+        }
+    }
+}
+
+impl Object for Cylinder {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let t = self.distance(ray.orig(), ray.dir(), t_min, t_max)?;
+        let point = ray.point(t);
+        Some(Hit::new(
+            ray.dir(),
+            t,
+            point,
+            self.norm(point),
+            self.uv(point),
+            &self.material,
+        ))
+    }
+
+    fn center(&self) -> Vec3 {
+        self.frame.origin() + self.frame.axis() * (self.height * 0.5)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let axis = self.frame.axis();
+        let base = self.frame.origin();
+        Aabb::disk(base, axis, self.radius).union(&Aabb::disk(
+            base + axis * self.height,
+            axis,
+            self.radius,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::cylinder::Cylinder;
+    use crate::objects::object::Object;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    fn cylinder() -> Cylinder {
+        Cylinder::new(
+            Vec3::new(0.0, -1.0, -5.0),
+            Vec3::new(0.0, 1.0, -5.0),
+            1.0,
+            Material::default(),
+        )
+    }
+
+    #[test]
+    fn test_cylinder_side() {
+        let cylinder = cylinder();
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        let t = cylinder
+            .distance(Vec3::new(0.0, 0.5, 0.0), dir, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 4.0).abs() < 1e-5);
+        assert!(cylinder
+            .distance(Vec3::new(0.0, 1.5, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        assert!(cylinder
+            .distance(Vec3::new(1.5, 0.0, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+
+        let n = cylinder.norm(Vec3::new(0.0, 0.5, -4.0));
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+        let (_, v) = cylinder.uv(Vec3::new(0.0, 0.5, -4.0));
+        assert!((v - 0.75).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_cylinder_caps() {
+        let cylinder = cylinder();
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let t = cylinder
+            .distance(Vec3::new(0.5, 5.0, -5.0), down, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 4.0).abs() < 1e-5);
+        assert!(cylinder
+            .distance(Vec3::new(1.5, 5.0, -5.0), down, EPS, f32::INFINITY)
+            .is_none());
+        // From the inside the bottom cap is hit.
+        let t = cylinder
+            .distance(Vec3::new(0.0, 0.0, -5.0), down, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 1.0).abs() < 1e-5);
+
+        let n = cylinder.norm(Vec3::new(0.5, 1.0, -5.0));
+        assert!((n - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+        let bounds = cylinder.bounds();
+        assert!((bounds.min() - Vec3::new(-1.0, -1.0, -6.0)).length() < 1e-5);
+        assert!((bounds.max() - Vec3::new(1.0, 1.0, -4.0)).length() < 1e-5);
+    }
+}
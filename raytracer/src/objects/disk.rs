@@ -0,0 +1,128 @@
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::frame::Frame;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+/// A flat circle around `center`, visible from both sides.
+#[derive(Clone)]
+pub struct Disk {
+    frame: Frame,
+    radius: f32,
+    material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: Material) -> Self {
+        Self {
+            frame: Frame::new(center, normal),
+            radius,
+            material,
+        }
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn distance(&self, orig: Vec3, dir: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let o = self.frame.to_local(orig);
+        let d = self.frame.vector_to_local(dir);
+        if d.z() == 0.0 {
+            return None;
+        }
+        let t = -o.z() / d.z();
+        let p = o + d * t;
+        if t > t_min && t <= t_max && p.x().powi(2) + p.y().powi(2) <= self.radius.powi(2) {
+            return Some(t);
+        }
+        None
+    }
+
+    fn norm(&self, _p: Vec3) -> Vec3 {
+        self.frame.axis()
+    }
+
+    // The disk is inscribed in the unit square.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = self.frame.to_local(p);
+        (
+            (local.x() / self.radius + 1.0) * 0.5,
+            (local.y() / self.radius + 1.0) * 0.5,
+        )
+    }
+}
+
+impl Object for Disk {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let t = self.distance(ray.orig(), ray.dir(), t_min, t_max)?;
+        let point = ray.point(t);
+        Some(Hit::new(
+            ray.dir(),
+            t,
+            point,
+            self.norm(point),
+            self.uv(point),
+            &self.material,
+        ))
+    }
+
+    fn center(&self) -> Vec3 {
+        self.frame.origin()
+    }
+
+    fn bounds(&self) -> Aabb {
+        Aabb::disk(self.frame.origin(), self.frame.axis(), self.radius)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::disk::Disk;
+    use crate::objects::object::Object;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_disk_intersect() {
+        let disk = Disk::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            Material::default(),
+        );
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        assert_eq!(
+            Some(5.0),
+            disk.distance(Vec3::new(0.5, 0.5, 0.0), dir, EPS, f32::INFINITY)
+        );
+        assert!(disk
+            .distance(Vec3::new(0.8, 0.8, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        assert!(disk
+            .distance(
+                Vec3::default(),
+                Vec3::new(1.0, 0.0, 0.0),
+                EPS,
+                f32::INFINITY
+            )
+            .is_none());
+        // Hit from behind as well.
+        assert_eq!(
+            Some(5.0),
+            disk.distance(Vec3::new(0.0, 0.0, -10.0), -dir, EPS, f32::INFINITY)
+        );
+
+        assert_eq!(
+            Vec3::new(0.0, 0.0, 1.0),
+            disk.norm(Vec3::new(0.0, 0.0, -5.0))
+        );
+        let (u, v) = disk.uv(Vec3::new(0.0, 0.0, -5.0));
+        assert!((u - 0.5).abs() < 1e-6 && (v - 0.5).abs() < 1e-6);
+        let bounds = disk.bounds();
+        assert_eq!(Vec3::new(-1.0, -1.0, -5.0), bounds.min());
+        assert_eq!(Vec3::new(1.0, 1.0, -5.0), bounds.max());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::raytracing::sampling::orthonormal_basis;
+use crate::vec3::Vec3;
+
+/// Local coordinate system of a rotationally symmetric object, with `z`
+/// along its axis.
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    origin: Vec3,
+    tangent: Vec3,
+    bitangent: Vec3,
+    axis: Vec3,
+}
+
+impl Frame {
+    pub fn new(origin: Vec3, axis: Vec3) -> Self {
+        let axis = axis.norm();
+        let (tangent, bitangent) = orthonormal_basis(axis);
+        Self {
+            origin,
+            tangent,
+            bitangent,
+            axis,
+        }
+    }
+
+    pub fn origin(&self) -> Vec3 {
+        self.origin
+    }
+
+    pub fn axis(&self) -> Vec3 {
+        self.axis
+    }
+
+    pub fn to_local(&self, p: Vec3) -> Vec3 {
+        self.vector_to_local(p - self.origin)
+    }
+
+    pub fn vector_to_local(&self, v: Vec3) -> Vec3 {
+        Vec3::new(v * self.tangent, v * self.bitangent, v * self.axis)
+    }
+
+    pub fn vector_to_world(&self, v: Vec3) -> Vec3 {
+        self.tangent * v.x() + self.bitangent * v.y() + self.axis * v.z()
+    }
+}
+
+/// Angle of the local point `p` around the frame axis, mapped to `[0, 1]`.
+pub fn azimuth(p: Vec3) -> f32 {
+    p.y().atan2(p.x()) / (2.0 * std::f32::consts::PI) + 0.5
+}
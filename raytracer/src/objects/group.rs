@@ -0,0 +1,104 @@
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::raytracing::bvh::Bvh;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Several objects with their own BVH that act as a single object, e.g. a
+/// mesh that is instanced many times.
+pub struct Group {
+    objects: Vec<Arc<dyn Object + Sync + Send>>,
+    bvh: Bvh,
+    bounds: Aabb,
+}
+
+impl Group {
+    pub fn new(objects: Vec<Arc<dyn Object + Sync + Send>>) -> Self {
+        Self {
+            bvh: Bvh::new(&objects),
+            bounds: objects
+                .iter()
+                .fold(Aabb::empty(), |b, o| b.union(&o.bounds())),
+            objects,
+        }
+    }
+
+    pub fn objects(&self) -> &Vec<Arc<dyn Object + Sync + Send>> {
+        &self.objects
+    }
+}
+
+impl Object for Group {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        self.bvh
+            .intersect(&self.objects, ray, t_min, t_max)
+            .map(|(_, hit)| hit)
+    }
+
+    fn center(&self) -> Vec3 {
+        self.bounds.centroid()
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::group::Group;
+    use crate::objects::object::Object;
+    use crate::objects::sphere::Sphere;
+    use crate::objects::triangle::Triangle;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_group() {
+        let mut red = Material::default();
+        red.set_diffuse_color(Vec3::new(1.0, 0.0, 0.0));
+        let floor = Triangle::new(
+            [
+                Vec3::new(-10.0, 0.0, 10.0),
+                Vec3::new(10.0, 0.0, 10.0),
+                Vec3::new(0.0, 0.0, -10.0),
+            ],
+            Material::default(),
+        );
+        let group = Group::new(vec![
+            Arc::new(floor),
+            Arc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, red)),
+        ]);
+        let down = Vec3::new(0.0, -1.0, 0.0);
+
+        let hit = group
+            .intersect(
+                &Ray::new(Vec3::new(0.0, 5.0, 0.0), down),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 3.0).abs() < 1e-5);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), hit.material.diffuse_color());
+        assert!((hit.normal - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+
+        // Beside the sphere only the floor is there.
+        let hit = group
+            .intersect(
+                &Ray::new(Vec3::new(3.0, 5.0, 0.0), down),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert_eq!(Vec3::default(), hit.material.diffuse_color());
+        assert_eq!(Vec3::new(0.0, 1.0, 0.0), hit.normal);
+        assert!(group
+            .intersect(&Ray::new(Vec3::new(0.0, 5.0, 0.0), down), EPS, 2.5)
+            .is_none());
+        assert_eq!(2, group.objects().len());
+    }
+}
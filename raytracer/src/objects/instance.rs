@@ -0,0 +1,165 @@
+use crate::mat4::Mat4;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Places a shared object in the scene with an affine transform. Rays are
+/// moved into the object's own space for intersection, and normals are
+/// brought back with the inverse transpose, so several instances can reuse
+/// one mesh without copying it.
+#[derive(Clone)]
+pub struct Instance {
+    object: Arc<dyn Object + Sync + Send>,
+    transform: Mat4,
+    inverse: Mat4,
+    normal_matrix: Mat4,
+}
+
+impl Instance {
+    /// Returns `None` if `transform` is not invertible.
+    pub fn new(object: Arc<dyn Object + Sync + Send>, transform: Mat4) -> Option<Self> {
+        let inverse = transform.inverse()?;
+        Some(Self {
+            object,
+            transform,
+            inverse,
+            normal_matrix: inverse.transpose(),
+        })
+    }
+
+    pub fn object(&self) -> &Arc<dyn Object + Sync + Send> {
+        &self.object
+    }
+
+    pub fn transform(&self) -> &Mat4 {
+        &self.transform
+    }
+}
+
+impl Object for Instance {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let local_dir = self.inverse.transform_vector(ray.dir());
+        // A unit step along the ray covers `scale` units in object space.
+        let scale = local_dir.length();
+        let local = ray.spawn(
+            self.inverse.transform_point(ray.orig()),
+            local_dir * (1.0 / scale),
+        );
+        let mut hit = self
+            .object
+            .intersect(&local, t_min * scale, t_max * scale)?;
+        hit.t /= scale;
+        hit.point = ray.point(hit.t);
+        hit.normal = self.normal_matrix.transform_vector(hit.normal).norm();
+        hit.shading_normal = self
+            .normal_matrix
+            .transform_vector(hit.shading_normal)
+            .norm();
+        Some(hit)
+    }
+
+    fn center(&self) -> Vec3 {
+        self.transform.transform_point(self.object.center())
+    }
+
+    fn bounds(&self) -> Aabb {
+        let local = self.object.bounds();
+        if !local.is_finite() {
+            return Aabb::infinite();
+        }
+        let (min, max) = (local.min(), local.max());
+        (0..8).fold(Aabb::empty(), |bounds, corner| {
+            let p = Vec3::new(
+                if corner & 1 == 0 { min.x() } else { max.x() },
+                if corner & 2 == 0 { min.y() } else { max.y() },
+                if corner & 4 == 0 { min.z() } else { max.z() },
+            );
+            bounds.grow(self.transform.transform_point(p))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mat4::Mat4;
+    use crate::material::Material;
+    use crate::objects::instance::Instance;
+    use crate::objects::object::Object;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_instance_ellipsoid() {
+        let sphere = Arc::new(Sphere::new(Vec3::default(), 1.0, Material::default()));
+        // An ellipsoid with half axes 2, 1, 1 around (0, 0, -10).
+        let transform =
+            Mat4::translation(Vec3::new(0.0, 0.0, -10.0)) * Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let instance = Instance::new(sphere, transform).unwrap();
+        let distance = |orig: Vec3, dir: Vec3| {
+            instance
+                .intersect(&Ray::new(orig, dir), EPS, f32::INFINITY)
+                .map(|hit| hit.t)
+        };
+
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let d = distance(Vec3::new(0.0, 0.0, 10.0), forward).unwrap();
+        assert!((d - 19.0).abs() < 1e-4);
+        let d = distance(Vec3::new(10.0, 0.0, -10.0), Vec3::new(-1.0, 0.0, 0.0)).unwrap();
+        assert!((d - 8.0).abs() < 1e-4);
+        assert!(distance(Vec3::new(1.5, 0.0, 10.0), forward).is_some());
+        assert!(distance(Vec3::new(0.0, 1.5, 10.0), forward).is_none());
+        // The range is measured along the ray in world space.
+        assert!(instance
+            .intersect(&Ray::new(Vec3::new(0.0, 0.0, 10.0), forward), EPS, 18.0)
+            .is_none());
+
+        // The normal on the flat side of the ellipsoid tilts away from the
+        // long axis.
+        let p = Vec3::new(2.0_f32.sqrt(), 2.0_f32.sqrt() / 2.0, -10.0);
+        let tilted = Vec3::new(1.0, 2.0, 0.0).norm();
+        let hit = instance
+            .intersect(&Ray::new(p + tilted * 5.0, -tilted), EPS, f32::INFINITY)
+            .unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-4);
+        assert!((hit.point - p).length() < 1e-4);
+        assert!((hit.normal - tilted).length() < 1e-4, "{:?}", hit.normal);
+        assert!(hit.front_face);
+
+        let bounds = instance.bounds();
+        assert!((bounds.min() - Vec3::new(-2.0, -1.0, -11.0)).length() < 1e-5);
+        assert!((bounds.max() - Vec3::new(2.0, 1.0, -9.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_instance_rotation() {
+        let sphere = Arc::new(Sphere::new(
+            Vec3::new(3.0, 0.0, 0.0),
+            1.0,
+            Material::default(),
+        ));
+        let instance =
+            Instance::new(sphere, Mat4::rotation(Vec3::new(0.0, 1.0, 0.0), PI / 2.0)).unwrap();
+        assert!((instance.center() - Vec3::new(0.0, 0.0, -3.0)).length() < 1e-5);
+        let hit = instance
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 2.0).abs() < 1e-5);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_instance_singular() {
+        let sphere = Arc::new(Sphere::new(Vec3::default(), 1.0, Material::default()));
+        assert!(Instance::new(sphere, Mat4::scaling(Vec3::new(1e-13, 1.0, 1.0))).is_none());
+    }
+}
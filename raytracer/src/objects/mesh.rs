@@ -0,0 +1,440 @@
+use crate::material::Material;
+use crate::objects::object::Object;
+use crate::objects::triangle::Triangle;
+use crate::vec3::Vec3;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(PathBuf, io::Error),
+    Parse {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            MeshError::Parse {
+                path,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MeshError::Io(_, err) => Some(err),
+            MeshError::Parse { .. } => None,
+        }
+    }
+}
+
+/// A triangle mesh loaded from a Wavefront OBJ file. The triangles are added
+/// to a scene one by one, so that they end up in the scene's BVH.
+pub struct Mesh {
+    triangles: Vec<Triangle>,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        Self { triangles }
+    }
+
+    pub fn triangles(&self) -> &Vec<Triangle> {
+        &self.triangles
+    }
+
+    pub fn into_objects(self) -> Vec<Arc<dyn Object + Sync + Send>> {
+        self.triangles
+            .into_iter()
+            .map(|t| Arc::new(t) as Arc<dyn Object + Sync + Send>)
+            .collect()
+    }
+
+    /// Loads an OBJ file together with the MTL libraries it references. If
+    /// `material` is given it overrides the materials of the file.
+    pub fn load_obj<P: AsRef<Path>>(
+        path: P,
+        material: Option<Material>,
+    ) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|err| MeshError::Io(path.into(), err))?;
+        Self::parse_obj(&source, path, material)
+    }
+
+    /// Parses the contents of an OBJ file, `path` is used for error messages
+    /// and to resolve `mtllib` statements.
+    pub fn parse_obj(
+        source: &str,
+        path: &Path,
+        material: Option<Material>,
+    ) -> Result<Self, MeshError> {
+        let error = |line: usize, message: String| MeshError::Parse {
+            path: path.into(),
+            line,
+            message,
+        };
+
+        let mut positions: Vec<Vec3> = Vec::new();
+        let mut normals: Vec<Vec3> = Vec::new();
+        let mut uvs: Vec<(f32, f32)> = Vec::new();
+        let mut materials: HashMap<String, Material> = HashMap::new();
+        let fixed = material.is_some();
+        let mut current = material.unwrap_or_else(default_material);
+        let mut triangles = Vec::new();
+
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let mut tokens = strip_comment(text).split_whitespace();
+            let statement = match tokens.next() {
+                Some(statement) => statement,
+                None => continue,
+            };
+            let args: Vec<&str> = tokens.collect();
+
+            match statement {
+                "v" => positions.push(parse_vec3(&args).map_err(|m| error(line, m))?),
+                "vn" => normals.push(parse_vec3(&args).map_err(|m| error(line, m))?),
+                "vt" => uvs.push(parse_uv(&args).map_err(|m| error(line, m))?),
+                "f" => {
+                    if args.len() < 3 {
+                        return Err(error(line, "a face needs at least 3 vertices".to_string()));
+                    }
+                    let vertices = args
+                        .iter()
+                        .map(|arg| parse_vertex(arg, positions.len(), uvs.len(), normals.len()))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|m| error(line, m))?;
+
+                    // Polygons are triangulated as a fan around the first vertex.
+                    for i in 1..vertices.len() - 1 {
+                        let face = [vertices[0], vertices[i], vertices[i + 1]];
+                        let corners = face.map(|(p, _, _)| positions[p]);
+                        let mut triangle = match face.map(|(_, _, n)| n) {
+                            [Some(n0), Some(n1), Some(n2)] => Triangle::with_normals(
+                                corners,
+                                [normals[n0], normals[n1], normals[n2]],
+                                current.clone(),
+                            ),
+                            _ => Triangle::new(corners, current.clone()),
+                        };
+                        if let [Some(t0), Some(t1), Some(t2)] = face.map(|(_, t, _)| t) {
+                            triangle.set_uvs([uvs[t0], uvs[t1], uvs[t2]]);
+                        }
+                        triangles.push(triangle);
+                    }
+                }
+                "mtllib" if !fixed => {
+                    let base = path.parent().unwrap_or_else(|| Path::new(""));
+                    for library in args {
+                        materials.extend(load_mtl(&base.join(library))?);
+                    }
+                }
+                "usemtl" if !fixed => {
+                    let name = args.join(" ");
+                    current = materials
+                        .get(&name)
+                        .cloned()
+                        .ok_or_else(|| error(line, format!("unknown material `{}`", name)))?;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self::new(triangles))
+    }
+}
+
+fn default_material() -> Material {
+    MtlMaterial::default().material()
+}
+
+fn strip_comment(text: &str) -> &str {
+    match text.find('#') {
+        Some(comment) => &text[..comment],
+        None => text,
+    }
+}
+
+fn parse_f32(arg: &str) -> Result<f32, String> {
+    arg.parse::<f32>()
+        .map_err(|_| format!("expected a number, got `{}`", arg))
+}
+
+fn parse_vec3(args: &[&str]) -> Result<Vec3, String> {
+    if args.len() < 3 {
+        return Err(format!("expected 3 numbers, got {}", args.len()));
+    }
+    Ok(Vec3::new(
+        parse_f32(args[0])?,
+        parse_f32(args[1])?,
+        parse_f32(args[2])?,
+    ))
+}
+
+// Texture coordinates `u v`, an optional third coordinate is ignored.
+fn parse_uv(args: &[&str]) -> Result<(f32, f32), String> {
+    if args.len() < 2 {
+        return Err(format!("expected 2 numbers, got {}", args.len()));
+    }
+    Ok((parse_f32(args[0])?, parse_f32(args[1])?))
+}
+
+// Resolves a 1-based or negative (relative to the end) OBJ index.
+fn parse_index(arg: &str, count: usize) -> Result<usize, String> {
+    let index = arg
+        .parse::<i64>()
+        .map_err(|_| format!("expected an index, got `{}`", arg))?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(format!("index {} is out of range", index));
+    }
+    Ok(resolved as usize)
+}
+
+// Parses a face vertex of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+fn parse_vertex(
+    arg: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<(usize, Option<usize>, Option<usize>), String> {
+    let mut parts = arg.split('/');
+    let position = parse_index(parts.next().unwrap_or_default(), positions)?;
+    let uv = match parts.next() {
+        Some(uv) if !uv.is_empty() => Some(parse_index(uv, uvs)?),
+        _ => None,
+    };
+    let normal = match parts.next() {
+        Some(normal) if !normal.is_empty() => Some(parse_index(normal, normals)?),
+        _ => None,
+    };
+    Ok((position, uv, normal))
+}
+
+fn load_mtl(path: &Path) -> Result<HashMap<String, Material>, MeshError> {
+    let source = fs::read_to_string(path).map_err(|err| MeshError::Io(path.into(), err))?;
+    parse_mtl(&source, path)
+}
+
+#[derive(Clone)]
+struct MtlMaterial {
+    diffuse: Vec3,
+    specular: Vec3,
+    specular_exponent: f32,
+    refractive_index: f32,
+    opacity: f32,
+    illum: u32,
+}
+
+impl MtlMaterial {
+    // Maps the illumination model onto the Phong weights of `Material`: the
+    // specular weight comes from `Ks`, models 3, 5 and 7 add a mirror
+    // reflection of the same strength, and transparency becomes refraction.
+    fn material(&self) -> Material {
+        let specular = self
+            .specular
+            .x()
+            .max(self.specular.y())
+            .max(self.specular.z());
+        let reflect = if matches!(self.illum, 3 | 5 | 7) {
+            specular
+        } else {
+            0.0
+        };
+        Material::new(
+            self.refractive_index,
+            [self.opacity, specular, reflect, 1.0 - self.opacity],
+            self.diffuse,
+            self.specular_exponent,
+        )
+    }
+}
+
+impl Default for MtlMaterial {
+    fn default() -> Self {
+        Self {
+            diffuse: Vec3::new(0.6, 0.6, 0.6),
+            specular: Vec3::default(),
+            specular_exponent: 0.0,
+            refractive_index: 1.0,
+            opacity: 1.0,
+            illum: 2,
+        }
+    }
+}
+
+fn parse_mtl(source: &str, path: &Path) -> Result<HashMap<String, Material>, MeshError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlMaterial)> = None;
+
+    for (index, text) in source.lines().enumerate() {
+        let error = |message: String| MeshError::Parse {
+            path: path.into(),
+            line: index + 1,
+            message,
+        };
+        let mut tokens = strip_comment(text).split_whitespace();
+        let statement = match tokens.next() {
+            Some(statement) => statement,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if statement == "newmtl" {
+            if let Some((name, mtl)) = current.take() {
+                materials.insert(name, mtl.material());
+            }
+            current = Some((args.join(" "), MtlMaterial::default()));
+            continue;
+        }
+
+        let mtl = match current.as_mut() {
+            Some((_, mtl)) => mtl,
+            None => return Err(error(format!("`{}` before `newmtl`", statement))),
+        };
+        let number = || parse_f32(args.first().copied().unwrap_or_default()).map_err(error);
+        match statement {
+            "Kd" => mtl.diffuse = parse_vec3(&args).map_err(error)?,
+            "Ks" => mtl.specular = parse_vec3(&args).map_err(error)?,
+            "Ns" => mtl.specular_exponent = number()?,
+            "Ni" => mtl.refractive_index = number()?,
+            "d" => mtl.opacity = number()?,
+            "Tr" => mtl.opacity = 1.0 - number()?,
+            "illum" => mtl.illum = number()? as u32,
+            _ => {}
+        }
+    }
+
+    if let Some((name, mtl)) = current {
+        materials.insert(name, mtl.material());
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::mesh::{parse_mtl, Mesh, MeshError};
+    use crate::objects::object::Object;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::path::Path;
+
+    const QUAD: &str = "\
+        # a unit quad facing +z\n\
+        v 0 0 0\n\
+        v 1 0 0\n\
+        v 1 1 0\n\
+        v 0 1 0\n\
+        vn 0 0 1\n\
+        vt 0 0\n\
+        f 1//1 2//1 3//1 4//1\n\
+        f -4 -3 -2\n";
+
+    #[test]
+    fn test_parse_obj() {
+        let mesh = Mesh::parse_obj(QUAD, Path::new("quad.obj"), None).unwrap();
+        assert_eq!(3, mesh.triangles().len());
+        assert_eq!(
+            [
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0)
+            ],
+            *mesh.triangles()[1].vertices()
+        );
+        let hit = mesh.triangles()[0]
+            .intersect(
+                &Ray::new(Vec3::new(0.9, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert_eq!(1.0, hit.t);
+    }
+
+    #[test]
+    fn test_parse_obj_uvs() {
+        // A quad whose texture is mirrored horizontally, the second triangle
+        // has no texture coordinates.
+        let source = "\
+            v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            vt 1 0\nvt 0 0\nvt 0 1 0\nvt 1 1\n\
+            f 1/1 2/2 3/3 4/4\n\
+            f 1 2 3\n";
+        let mesh = Mesh::parse_obj(source, Path::new("quad.obj"), None).unwrap();
+        let uv = |triangle: usize, x: f32, y: f32| {
+            mesh.triangles()[triangle]
+                .intersect(
+                    &Ray::new(Vec3::new(x, y, 1.0), Vec3::new(0.0, 0.0, -1.0)),
+                    EPS,
+                    f32::INFINITY,
+                )
+                .unwrap()
+                .uv
+        };
+        let (u, v) = uv(0, 0.75, 0.25);
+        assert!((u - 0.25).abs() < 1e-6 && (v - 0.25).abs() < 1e-6);
+        let (u, v) = uv(1, 0.25, 0.75);
+        assert!((u - 0.75).abs() < 1e-6 && (v - 0.75).abs() < 1e-6);
+        assert_eq!((0.5, 0.25), uv(2, 0.75, 0.25));
+
+        assert!(Mesh::parse_obj("v 0 0 0\nvt 0\n", Path::new("bad.obj"), None).is_err());
+        assert!(Mesh::parse_obj("v 0 0 0\nf 1/1 1/1 1/1\n", Path::new("bad.obj"), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_obj_errors() {
+        for (source, line) in [("v 0 0 0\nf 1 2 3", 2), ("v 0 0\n", 1), ("f 1 2", 1)] {
+            match Mesh::parse_obj(source, Path::new("bad.obj"), None) {
+                Err(MeshError::Parse { line: l, .. }) => assert_eq!(line, l),
+                _ => panic!("`{}` should not parse", source),
+            }
+        }
+        assert!(Mesh::parse_obj("usemtl missing", Path::new("bad.obj"), None).is_err());
+        assert!(Mesh::parse_obj(
+            "usemtl missing",
+            Path::new("bad.obj"),
+            Some(Material::default())
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_parse_mtl() {
+        let materials = parse_mtl(
+            "newmtl red\nKd 1 0 0\nKs 0.5 0.5 0.5\nNs 50\n\nnewmtl glass\nNi 1.5\nd 0.2\nillum 7\nKs 1 1 1\n",
+            Path::new("test.mtl"),
+        )
+        .unwrap();
+
+        let red = &materials["red"];
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), red.diffuse_color());
+        assert_eq!([1.0, 0.5, 0.0, 0.0], *red.albedo());
+        assert_eq!(50.0, red.specular_exponent());
+
+        let glass = &materials["glass"];
+        assert_eq!(1.5, glass.refractive_index());
+        assert_eq!([0.2, 1.0, 1.0, 0.8], *glass.albedo());
+    }
+}
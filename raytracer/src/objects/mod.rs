@@ -1,3 +1,17 @@
+pub mod aabb;
+pub mod cone;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
+pub mod frame;
+pub mod group;
+pub mod instance;
+pub mod mesh;
+pub mod moving;
 pub mod object;
 pub mod plane;
+pub mod roots;
 pub mod sphere;
+pub mod torus;
+pub mod triangle;
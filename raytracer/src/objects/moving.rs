@@ -0,0 +1,157 @@
+use crate::animation::Keyframes;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// An object that moves along a path of keyframed offsets. A ray sees the
+/// object where it is at the time of the ray, so rays spread over the
+/// shutter interval blur it along its path.
+pub struct Moving {
+    object: Arc<dyn Object + Sync + Send>,
+    path: Keyframes<Vec3>,
+    bounds: Aabb,
+}
+
+impl Moving {
+    pub fn new(object: Arc<dyn Object + Sync + Send>, path: Keyframes<Vec3>) -> Self {
+        // The path stays within the hull of its control points, so the boxes
+        // moved to those points cover the object at every time.
+        let local = object.bounds();
+        let bounds = path
+            .hull()
+            .into_iter()
+            .fold(Aabb::empty(), |bounds, offset| {
+                bounds.union(&local.translate(offset))
+            });
+        Self {
+            object,
+            path,
+            bounds,
+        }
+    }
+
+    pub fn object(&self) -> &Arc<dyn Object + Sync + Send> {
+        &self.object
+    }
+
+    pub fn path(&self) -> &Keyframes<Vec3> {
+        &self.path
+    }
+
+    // The ray moved into the frame of the object at the time of the ray.
+    fn local(&self, ray: &Ray) -> (Ray, Vec3) {
+        let offset = self.path.at(ray.time());
+        (ray.spawn(ray.orig() - offset, ray.dir()), offset)
+    }
+}
+
+impl Object for Moving {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let (local, offset) = self.local(ray);
+        let mut hit = self.object.intersect(&local, t_min, t_max)?;
+        hit.point = hit.point + offset;
+        Some(hit)
+    }
+
+    fn center(&self) -> Vec3 {
+        let keys = self.path.keys();
+        let middle = (keys[0].0 + keys[keys.len() - 1].0) / 2.0;
+        self.object.center() + self.path.at(middle)
+    }
+
+    fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<(f32, f32)> {
+        self.object.intervals(&self.local(ray).0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{Interpolation, Keyframes};
+    use crate::material::Material;
+    use crate::objects::moving::Moving;
+    use crate::objects::object::Object;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::raytracing::bvh::Bvh;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_moving_sphere() {
+        let sphere = Arc::new(Sphere::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            1.0,
+            Material::default(),
+        ));
+        // Two units to the right over the shutter interval.
+        let path = Keyframes::new(vec![
+            (0.0, Vec3::default()),
+            (1.0, Vec3::new(2.0, 0.0, 0.0)),
+        ]);
+        let moving = Moving::new(sphere, path);
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let hit = |x: f32, time: f32| {
+            moving
+                .intersect(
+                    &Ray::at_time(Vec3::new(x, 0.0, 0.0), forward, time),
+                    EPS,
+                    f32::INFINITY,
+                )
+                .map(|hit| (hit.t, hit.point, hit.normal))
+        };
+
+        let (t, point, normal) = hit(2.0, 1.0).unwrap();
+        assert!((t - 4.0).abs() < 1e-5);
+        assert_eq!(Vec3::new(2.0, 0.0, -4.0), point);
+        assert!((normal - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+        assert!(hit(2.0, 0.0).is_none());
+        assert!(hit(1.0, 0.5).is_some());
+        assert!(hit(-0.5, 1.0).is_none());
+
+        let bounds = moving.bounds();
+        assert_eq!(Vec3::new(-1.0, -1.0, -6.0), bounds.min());
+        assert_eq!(Vec3::new(3.0, 1.0, -4.0), bounds.max());
+        assert_eq!(Vec3::new(1.0, 0.0, -5.0), moving.center());
+
+        let spans = moving.intervals(&Ray::at_time(Vec3::new(2.0, 0.0, 0.0), forward, 1.0));
+        assert_eq!(1, spans.len());
+        assert!((spans[0].0 - 4.0).abs() < 1e-5 && (spans[0].1 - 6.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_moving_catmull_rom_overshoot() {
+        let sphere = Arc::new(Sphere::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            1.0,
+            Material::default(),
+        ));
+        let mut path = Keyframes::new(vec![
+            (0.0, Vec3::default()),
+            (1.0, Vec3::new(4.0, 0.0, 0.0)),
+            (2.0, Vec3::new(4.0, 0.0, 0.0)),
+        ]);
+        path.set_interpolation(Interpolation::CatmullRom);
+        // The curve swings past the last key on its way there.
+        let overshoot = path.at(1.5).x();
+        assert!(overshoot > 4.2);
+        let objects: Vec<Arc<dyn Object + Sync + Send>> = vec![Arc::new(Moving::new(sphere, path))];
+        assert!(objects[0].bounds().max().x() >= overshoot + 1.0);
+
+        // A ray grazing the far side of the sphere at the overshoot time
+        // still gets past the bounding volumes.
+        let bvh = Bvh::new(&objects);
+        let ray = Ray::at_time(
+            Vec3::new(overshoot + 0.95, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            1.5,
+        );
+        assert!(bvh.intersect(&objects, &ray, EPS, f32::INFINITY).is_some());
+    }
+}
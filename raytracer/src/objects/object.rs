@@ -1,12 +1,90 @@
 use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::ray::Ray;
+use crate::raytracing::util::EPS;
 use crate::vec3::Vec3;
 
+// Upper bound on the surface crossings `Object::intervals` follows along a
+// ray, in case rounding keeps it from getting past a surface.
+const MAX_CROSSINGS: usize = 64;
+
+/// Where and how a ray hits the surface of an object. Both normals point out
+/// of the object whichever side the ray comes from, `front_face` tells if it
+/// comes from the outside.
+#[derive(Clone, Copy)]
+pub struct Hit<'a> {
+    /// Distance along the ray, in units of its direction.
+    pub t: f32,
+    pub point: Vec3,
+    /// Normal of the actual surface.
+    pub normal: Vec3,
+    /// Normal used for lighting, e.g. interpolated between mesh vertices.
+    pub shading_normal: Vec3,
+    pub uv: (f32, f32),
+    pub front_face: bool,
+    pub material: &'a Material,
+}
+
+impl<'a> Hit<'a> {
+    /// A hit whose shading normal is the geometric one.
+    pub fn new(
+        dir: Vec3,
+        t: f32,
+        point: Vec3,
+        normal: Vec3,
+        uv: (f32, f32),
+        material: &'a Material,
+    ) -> Self {
+        Self {
+            t,
+            point,
+            normal,
+            shading_normal: normal,
+            uv,
+            front_face: dir * normal < 0.0,
+            material,
+        }
+    }
+}
+
 pub trait Object {
-    fn intersect(&self, orig: Vec3, dir: Vec3) -> (bool, f32);
+    /// Returns the nearest hit of the ray with a distance in
+    /// `(t_min, t_max]`.
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>>;
 
     fn center(&self) -> Vec3;
 
-    fn material(&self, p: Vec3) -> Material;
+    /// A box that contains the object at any time, moving objects included.
+    fn bounds(&self) -> Aabb;
 
-    fn norm(&self, p: Vec3) -> Vec3;
+    /// Sorted spans of the ray that lie inside the object, as pairs of entry
+    /// and exit distances. A span that contains the ray origin starts at
+    /// negative infinity and one that never ends at infinity.
+    ///
+    /// The default implementation follows the ray from surface to surface
+    /// and tells entries from exits by the normal, which only makes sense
+    /// for closed objects.
+    fn intervals(&self, ray: &Ray) -> Vec<(f32, f32)> {
+        let mut spans = Vec::new();
+        let mut entry = None;
+        let mut t_min = EPS;
+        for _ in 0..MAX_CROSSINGS {
+            let hit = match self.intersect(ray, t_min, f32::INFINITY) {
+                Some(hit) => hit,
+                None => break,
+            };
+            t_min = hit.t + EPS;
+            // A ray that only grazes the surface stays on the same side.
+            let cos = ray.dir() * hit.normal;
+            if cos < -1e-6 {
+                entry = Some(hit.t);
+            } else if cos > 1e-6 {
+                spans.push((entry.take().unwrap_or(f32::NEG_INFINITY), hit.t));
+            }
+        }
+        if let Some(start) = entry {
+            spans.push((start, f32::INFINITY));
+        }
+        spans
+    }
 }
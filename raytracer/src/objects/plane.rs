@@ -1,57 +1,291 @@
 use crate::material::Material;
-use crate::objects::object::Object;
-use crate::raytracing::util::EPS;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::raytracing::sampling::orthonormal_basis;
+use crate::textures::checker::Checker;
+use crate::textures::solid::SolidColor;
 use crate::vec3::Vec3;
+use std::sync::Arc;
 
+/// A flat surface through `center`, either unbounded or a rectangle spanned
+/// by its tangent and bitangent.
 #[derive(Clone)]
 pub struct Plane {
     center: Vec3,
     normal: Vec3,
-    size: f32,
+    tangent: Vec3,
+    bitangent: Vec3,
+    // Width along the tangent and height along the bitangent, `None` for an
+    // infinite plane.
+    extent: Option<(f32, f32)>,
+    material: Material,
 }
 
 impl Plane {
-    pub fn new(center: Vec3, normal: Vec3, size: f32) -> Self {
+    /// A `size` by `size` square. Its edges follow the x axis where the
+    /// plane allows it, so a horizontal square is aligned with x and z.
+    pub fn new(center: Vec3, normal: Vec3, size: f32, material: Material) -> Self {
+        let tangent = Vec3::new(1.0, 0.0, 0.0);
+        Self::rectangle(center, normal, tangent, size, size, material)
+    }
+
+    /// A `width` by `height` rectangle with the width running along
+    /// `tangent`.
+    pub fn rectangle(
+        center: Vec3,
+        normal: Vec3,
+        tangent: Vec3,
+        width: f32,
+        height: f32,
+        material: Material,
+    ) -> Self {
+        let mut plane = Self::infinite(center, normal, material);
+        plane.set_tangent(tangent);
+        plane.extent = Some((width, height));
+        plane
+    }
+
+    pub fn infinite(center: Vec3, normal: Vec3, material: Material) -> Self {
+        let normal = normal.norm();
+        let (bitangent, tangent) = orthonormal_basis(normal);
         Self {
             center,
             normal,
-            size,
+            tangent,
+            bitangent,
+            extent: None,
+            material,
+        }
+    }
+
+    /// Orange and blue checkerboard with `squares` squares per UV unit.
+    pub fn checkerboard(squares: f32) -> Material {
+        let mut material = Material::new(1.0, [2.0, 0.0, 0.0, 0.0], Vec3::default(), 0.0);
+        material.set_texture(Some(Arc::new(Checker::new(
+            Arc::new(SolidColor::new(Vec3::new(0.3, 0.1, 0.0))),
+            Arc::new(SolidColor::new(Vec3::new(0.1, 0.1, 0.4))),
+            squares,
+        ))));
+        material
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.normal
+    }
+
+    pub fn tangent(&self) -> Vec3 {
+        self.tangent
+    }
+
+    /// Turns the plane around its normal so that `u` runs along `tangent`
+    /// projected into the plane. A tangent along the normal is ignored.
+    pub fn set_tangent(&mut self, tangent: Vec3) {
+        let projected = tangent - self.normal * (tangent * self.normal);
+        if projected.length() > 1e-3 * tangent.length() {
+            self.tangent = projected.norm();
+            self.bitangent = self.tangent.cross(self.normal);
+        }
+    }
+
+    /// Width and height of a rectangle, `None` if the plane is infinite.
+    pub fn extent(&self) -> Option<(f32, f32)> {
+        self.extent
+    }
+}
+
+impl Plane {
+    // A rectangle maps onto `[0, 1]^2`, `u` along the tangent and `v` along
+    // the bitangent. An infinite plane uses distances from the center.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = p - self.center;
+        let (u, v) = (local * self.tangent, local * self.bitangent);
+        match self.extent {
+            Some((width, height)) => (u / width + 0.5, v / height + 0.5),
+            None => (u, v),
         }
     }
 }
 
 impl Object for Plane {
-    fn intersect(&self, orig: Vec3, dir: Vec3) -> (bool, f32) {
-        if (dir.y()).abs() > EPS {
-            let d = -(orig.y() - self.center.y()) / dir.y();
-            let p = orig + dir * d;
-            let x_bounds = self.center.x() - self.size / 2.0 < p.x()
-                && p.x() < self.center.x() + self.size / 2.0;
-            let z_bounds = self.center.z() - self.size / 2.0 < p.z()
-                && p.z() < self.center.z() + self.size / 2.0;
-            if d > EPS && x_bounds && z_bounds {
-                return (true, d);
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let (orig, dir) = (ray.orig(), ray.dir());
+        let cos = dir * self.normal;
+        if cos.abs() < 1e-6 {
+            return None;
+        }
+        let t = (self.center - orig) * self.normal / cos;
+        if t <= t_min || t > t_max {
+            return None;
+        }
+        let point = orig + dir * t;
+        if let Some((width, height)) = self.extent {
+            let local = point - self.center;
+            if (local * self.tangent).abs() >= width / 2.0
+                || (local * self.bitangent).abs() >= height / 2.0
+            {
+                return None;
             }
         }
-
-        (false, 0.0)
+        Some(Hit::new(
+            dir,
+            t,
+            point,
+            self.normal,
+            self.uv(point),
+            &self.material,
+        ))
     }
 
     fn center(&self) -> Vec3 {
         self.center
     }
 
-    fn material(&self, p: Vec3) -> Material {
-        let diffusive = if ((p.x() * p.z()) as i32) & 33 == 1 {
-            Vec3::new(0.3, 0.1, 0.0)
-        } else {
-            Vec3::new(0.1, 0.1, 0.4)
-        };
+    fn bounds(&self) -> Aabb {
+        match self.extent {
+            Some((width, height)) => {
+                let (u, v) = (
+                    self.tangent * (width / 2.0),
+                    self.bitangent * (height / 2.0),
+                );
+                Aabb::new(self.center - u - v, self.center + u + v)
+                    .grow(self.center + u - v)
+                    .grow(self.center - u + v)
+            }
+            // Only a plane perpendicular to an axis is bounded along it.
+            None => {
+                let extent = |axis: usize| {
+                    if self.normal[axis].abs() == 1.0 {
+                        0.0
+                    } else {
+                        f32::INFINITY
+                    }
+                };
+                let half = Vec3::new(extent(0), extent(1), extent(2));
+                Aabb::new(self.center - half, self.center + half)
+            }
+        }
+    }
+}
 
-        Material::new(1.0, [2.0, 0.0, 0.0, 0.0], diffusive, 0.0)
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::object::Object;
+    use crate::objects::plane::Plane;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    fn distance(plane: &Plane, orig: Vec3, dir: Vec3) -> Option<f32> {
+        plane
+            .intersect(&Ray::new(orig, dir), EPS, f32::INFINITY)
+            .map(|hit| hit.t)
     }
 
-    fn norm(&self, _p: Vec3) -> Vec3 {
-        self.normal
+    #[test]
+    fn test_plane_uv() {
+        let plane = Plane::new(
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            4.0,
+            Plane::checkerboard(4.0),
+        );
+        assert_eq!((0.5, 0.5), plane.uv(Vec3::new(1.0, 0.0, 2.0)));
+        assert_eq!((0.0, 1.0), plane.uv(Vec3::new(-1.0, 0.0, 4.0)));
+
+        let hit = plane
+            .intersect(
+                &Ray::new(Vec3::new(0.5, 1.0, 2.5), Vec3::new(0.0, -1.0, 0.0)),
+                EPS,
+                2.0,
+            )
+            .unwrap();
+        let (p, (u, v), material) = (hit.point, hit.uv, hit.material);
+        assert_ne!(
+            material.diffuse_color_at(u, v, p),
+            material.diffuse_color_at(u + 0.25, v, p)
+        );
+    }
+
+    #[test]
+    fn test_plane_any_normal() {
+        // A wall facing +x.
+        let wall = Plane::new(
+            Vec3::new(-5.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            4.0,
+            Material::default(),
+        );
+        let left = Vec3::new(-1.0, 0.0, 0.0);
+        let hit = wall
+            .intersect(
+                &Ray::new(Vec3::new(0.0, 1.0, 1.0), left),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert_eq!(5.0, hit.t);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), hit.normal);
+        assert!(hit.front_face);
+        assert!(distance(&wall, Vec3::new(0.0, 3.0, 0.0), left).is_none());
+        assert!(distance(&wall, Vec3::default(), Vec3::new(0.0, 1.0, 0.0)).is_none());
+        // Seen from behind, the normal still points to +x.
+        let hit = wall
+            .intersect(
+                &Ray::new(Vec3::new(-10.0, 0.0, 0.0), -left),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), hit.normal);
+        assert!(!hit.front_face);
+        let bounds = wall.bounds();
+        assert_eq!(Vec3::new(-5.0, -2.0, -2.0), bounds.min());
+        assert_eq!(Vec3::new(-5.0, 2.0, 2.0), bounds.max());
+
+        let tilted = Plane::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            2.0,
+            Material::default(),
+        );
+        let d = distance(&tilted, Vec3::new(0.0, 0.5, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert!(d.is_some_and(|d| (d - 5.5).abs() < 1e-5));
+    }
+
+    #[test]
+    fn test_plane_rectangle_and_infinite() {
+        let rectangle = Plane::rectangle(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            4.0,
+            1.0,
+            Material::default(),
+        );
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        // The long side runs along the diagonal.
+        assert!(distance(&rectangle, Vec3::new(1.2, 1.2, 0.0), forward).is_some());
+        assert!(distance(&rectangle, Vec3::new(1.2, -1.2, 0.0), forward).is_none());
+        let (u, v) = rectangle.uv(Vec3::new(1.0, 1.0, -5.0));
+        assert!((u - (0.5 + 2.0f32.sqrt() / 4.0)).abs() < 1e-5 && (v - 0.5).abs() < 1e-5);
+
+        let infinite = Plane::infinite(
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Material::default(),
+        );
+        let d = distance(
+            &infinite,
+            Vec3::new(1e4, 0.0, 0.0),
+            Vec3::new(1.0, -1e-4, 0.0).norm(),
+        );
+        assert!(d.is_some_and(|d| (d - 1e4).abs() < 1.0));
+        let bounds = infinite.bounds();
+        assert_eq!(-1.0, bounds.min().y());
+        assert_eq!(-1.0, bounds.max().y());
+        assert_eq!(f32::INFINITY, bounds.max().x());
+        assert!(!bounds.is_finite());
     }
 }
@@ -0,0 +1,175 @@
+//! Real roots of polynomials for the intersection of quadric and quartic
+//! surfaces, after Jochen Schwarze's solver from Graphics Gems I.
+
+const EQN_EPS: f64 = 1e-9;
+
+fn is_zero(x: f64) -> bool {
+    x.abs() < EQN_EPS
+}
+
+/// Roots of `a x^2 + b x + c` in ascending order.
+pub fn quadratic(a: f32, b: f32, c: f32) -> Option<(f32, f32)> {
+    if a == 0.0 {
+        if b == 0.0 {
+            return None;
+        }
+        let x = -c / b;
+        return Some((x, x));
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    // Avoids cancellation between `-b` and the square root.
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (x0, x1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+    Some((x0.min(x1), x0.max(x1)))
+}
+
+// Roots of `x^2 + p x + q`.
+fn normalized_quadratic(p: f64, q: f64) -> Vec<f64> {
+    let half = p / 2.0;
+    let discriminant = half * half - q;
+    if is_zero(discriminant) {
+        vec![-half]
+    } else if discriminant < 0.0 {
+        Vec::new()
+    } else {
+        let root = discriminant.sqrt();
+        vec![root - half, -root - half]
+    }
+}
+
+// Roots of `x^3 + a x^2 + b x + c`.
+fn cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substitute x = y - a/3 to eliminate the quadric term: y^3 + 3p y + 2q.
+    let sq_a = a * a;
+    let p = (-sq_a / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * sq_a - a * b / 3.0 + c) / 2.0;
+    let cb_p = p * p * p;
+    let discriminant = q * q + cb_p;
+
+    let roots = if is_zero(discriminant) {
+        if is_zero(q) {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-cb_p).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        let third = std::f64::consts::PI / 3.0;
+        vec![
+            t * phi.cos(),
+            -t * (phi + third).cos(),
+            -t * (phi - third).cos(),
+        ]
+    } else {
+        let root = discriminant.sqrt();
+        vec![(root - q).cbrt() - (root + q).cbrt()]
+    };
+
+    roots.into_iter().map(|y| y - a / 3.0).collect()
+}
+
+/// Real roots of `x^4 + a x^3 + b x^2 + c x + d` in ascending order.
+pub fn quartic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    // Substitute x = y - a/4 to eliminate the cubic term:
+    // y^4 + p y^2 + q y + r.
+    let sq_a = a * a;
+    let p = -3.0 / 8.0 * sq_a + b;
+    let q = sq_a * a / 8.0 - a * b / 2.0 + c;
+    let r = -3.0 / 256.0 * sq_a * sq_a + sq_a * b / 16.0 - a * c / 4.0 + d;
+
+    let mut roots = if is_zero(r) {
+        // y (y^3 + p y + q) = 0
+        let mut roots = cubic(0.0, p, q);
+        roots.push(0.0);
+        roots
+    } else {
+        // Any root of the resolvent cubic splits the quartic into two
+        // quadratics.
+        let z = cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0)[0];
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if is_zero(u) {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if is_zero(v) {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if q < 0.0 { -v } else { v };
+        let mut roots = normalized_quadratic(v, z - u);
+        roots.extend(normalized_quadratic(-v, z + u));
+        roots
+    };
+
+    for x in roots.iter_mut() {
+        *x -= a / 4.0;
+        // The closed form loses precision, a few Newton steps recover it.
+        for _ in 0..2 {
+            let f = (((*x + a) * *x + b) * *x + c) * *x + d;
+            let df = ((4.0 * *x + 3.0 * a) * *x + 2.0 * b) * *x + c;
+            if df != 0.0 {
+                *x -= f / df;
+            }
+        }
+    }
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::objects::roots::{cubic, quadratic, quartic};
+
+    fn assert_roots(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len(), "{:?}", actual);
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(
+                (e - a).abs() < 1e-6,
+                "expected {:?}, got {:?}",
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_quadratic() {
+        assert_eq!(Some((-3.0, 2.0)), quadratic(1.0, 1.0, -6.0));
+        assert_eq!(Some((2.0, 2.0)), quadratic(0.0, 2.0, -4.0));
+        assert_eq!(None, quadratic(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_cubic() {
+        // (x - 1)(x - 2)(x + 3)
+        let mut roots = cubic(0.0, -7.0, 6.0);
+        roots.sort_by(f64::total_cmp);
+        assert_roots(&[-3.0, 1.0, 2.0], &roots);
+        assert_roots(&[2.0], &cubic(-2.0, 1.0, -2.0));
+    }
+
+    #[test]
+    fn test_quartic() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(&[1.0, 2.0, 3.0, 4.0], &quartic(-10.0, 35.0, -50.0, 24.0));
+        // (x^2 + 1)(x - 1)(x + 2)
+        assert_roots(&[-2.0, 1.0], &quartic(1.0, -1.0, 1.0, -2.0));
+        // x^4 + 1 has no real roots
+        assert_roots(&[], &quartic(0.0, 0.0, 0.0, 1.0));
+        // (x - 0.5) x (x + 1)(x - 7)
+        assert_roots(&[-1.0, 0.0, 0.5, 7.0], &quartic(-6.5, -4.0, 3.5, 0.0));
+    }
+}
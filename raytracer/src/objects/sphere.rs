@@ -1,7 +1,9 @@
 use crate::material::Material;
-use crate::objects::object::Object;
-use crate::raytracing::util::EPS;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
 use crate::vec3::Vec3;
+use std::f32::consts::PI;
 
 #[derive(Clone)]
 pub struct Sphere {
@@ -24,36 +26,97 @@ impl Sphere {
     }
 }
 
+impl Sphere {
+    // Longitude and latitude: `u` goes around the y axis starting at -x,
+    // `v` from the bottom pole to the top one.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let n = (p - self.center).norm();
+        let theta = (-n.y()).clamp(-1.0, 1.0).acos();
+        let phi = (-n.z()).atan2(n.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
 impl Object for Sphere {
-    fn intersect(&self, orig: Vec3, dir: Vec3) -> (bool, f32) {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let (orig, dir) = (ray.orig(), ray.dir());
         let l = self.center - orig;
         let tca = l * dir;
         let d2 = l * l - tca * tca;
         let r2 = self.radius.powi(2);
         if d2 > r2 {
-            return (false, 0.0);
+            return None;
         }
         let thc = (r2 - d2).sqrt();
-        let t0 = tca - thc;
-        let t1 = tca + thc;
-        if t0 > EPS {
-            return (true, t0);
-        }
-        if t1 > EPS {
-            return (true, t1);
-        }
-        (false, 0.0)
+        let t = [tca - thc, tca + thc]
+            .into_iter()
+            .find(|&t| t > t_min && t <= t_max)?;
+        let point = orig + dir * t;
+        let normal = (point - self.center).norm();
+        Some(Hit::new(
+            dir,
+            t,
+            point,
+            normal,
+            self.uv(point),
+            &self.material,
+        ))
     }
 
     fn center(&self) -> Vec3 {
         self.center
     }
 
-    fn material(&self, _p: Vec3) -> Material {
-        self.material
+    fn bounds(&self) -> Aabb {
+        Aabb::new(self.center - self.radius, self.center + self.radius)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::object::Object;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_sphere_uv() {
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, -5.0), 2.0, Material::default());
+        let (_, v) = sphere.uv(Vec3::new(0.0, 2.0, -5.0));
+        assert!((v - 1.0).abs() < 1e-6);
+        let (u, v) = sphere.uv(Vec3::new(-2.0, 0.0, -5.0));
+        assert!((v - 0.5).abs() < 1e-6);
+        assert!(u.abs() < 1e-6 || (u - 1.0).abs() < 1e-6);
+        let (u, _) = sphere.uv(Vec3::new(2.0, 0.0, -5.0));
+        assert!((u - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sphere_hit() {
+        let sphere = Sphere::new(Vec3::new(0.0, 0.0, -5.0), 2.0, Material::default());
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        let hit = sphere
+            .intersect(&Ray::new(Vec3::default(), dir), 0.0, f32::INFINITY)
+            .unwrap();
+        assert_eq!(3.0, hit.t);
+        assert_eq!(Vec3::new(0.0, 0.0, -3.0), hit.point);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), hit.normal);
+        assert!(hit.front_face);
+
+        // Beyond the near side the far one is found, from the inside.
+        let hit = sphere
+            .intersect(&Ray::new(Vec3::default(), dir), 3.5, f32::INFINITY)
+            .unwrap();
+        assert_eq!(7.0, hit.t);
+        assert_eq!(Vec3::new(0.0, 0.0, -1.0), hit.normal);
+        assert!(!hit.front_face);
 
-    fn norm(&self, p: Vec3) -> Vec3 {
-        (p - self.center()).norm()
+        assert!(sphere
+            .intersect(&Ray::new(Vec3::default(), dir), 0.0, 2.5)
+            .is_none());
+        assert!(sphere
+            .intersect(&Ray::new(Vec3::new(3.0, 0.0, 0.0), dir), 0.0, 10.0)
+            .is_none());
     }
 }
@@ -0,0 +1,183 @@
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::frame::{azimuth, Frame};
+use crate::objects::object::{Hit, Object};
+use crate::objects::roots::quartic;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::f32::consts::PI;
+
+/// A ring around `axis`: the circle of radius `minor_radius` swept along the
+/// circle of radius `major_radius`.
+#[derive(Clone)]
+pub struct Torus {
+    frame: Frame,
+    major_radius: f32,
+    minor_radius: f32,
+    material: Material,
+}
+
+impl Torus {
+    pub fn new(
+        center: Vec3,
+        axis: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        material: Material,
+    ) -> Self {
+        Self {
+            frame: Frame::new(center, axis),
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    pub fn major_radius(&self) -> f32 {
+        self.major_radius
+    }
+
+    pub fn minor_radius(&self) -> f32 {
+        self.minor_radius
+    }
+
+    fn distance(&self, orig: Vec3, dir: Vec3, t_min: f32, t_max: f32) -> Option<f32> {
+        let o = self.frame.to_local(orig);
+        let d = self.frame.vector_to_local(dir).norm();
+        let scale = 1.0 / self.frame.vector_to_local(dir).length();
+
+        // Start at the bounding sphere, the quartic loses precision when the
+        // ray origin is far away.
+        let skip = (o.length() - self.major_radius - self.minor_radius).max(0.0);
+        let o = o + d * skip;
+
+        let (ox, oy, oz) = (o.x() as f64, o.y() as f64, o.z() as f64);
+        let dz = d.z() as f64;
+        let r2 = (self.major_radius as f64).powi(2);
+        let f = ox * d.x() as f64 + oy * d.y() as f64 + oz * dz;
+        let e = ox * ox + oy * oy + oz * oz - r2 - (self.minor_radius as f64).powi(2);
+        let roots = quartic(
+            4.0 * f,
+            2.0 * e + 4.0 * f * f + 4.0 * r2 * dz * dz,
+            4.0 * f * e + 8.0 * r2 * oz * dz,
+            e * e + 4.0 * r2 * (oz * oz - (self.minor_radius as f64).powi(2)),
+        );
+
+        roots
+            .into_iter()
+            .map(|t| (t as f32 + skip) * scale)
+            .find(|&t| t > t_min && t <= t_max)
+    }
+
+    fn norm(&self, p: Vec3) -> Vec3 {
+        let local = self.frame.to_local(p);
+        let ring = Vec3::new(local.x(), local.y(), 0.0).norm() * self.major_radius;
+        self.frame.vector_to_world(local - ring).norm()
+    }
+
+    // `u` goes around the axis, `v` around the tube starting at its outer
+    // equator.
+    fn uv(&self, p: Vec3) -> (f32, f32) {
+        let local = self.frame.to_local(p);
+        let rho = (local.x().powi(2) + local.y().powi(2)).sqrt();
+        let v = local.z().atan2(rho - self.major_radius) / (2.0 * PI);
+        (azimuth(local), v.rem_euclid(1.0))
+    }
+}
+
+impl Object for Torus {
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let t = self.distance(ray.orig(), ray.dir(), t_min, t_max)?;
+        let point = ray.point(t);
+        Some(Hit::new(
+            ray.dir(),
+            t,
+            point,
+            self.norm(point),
+            self.uv(point),
+            &self.material,
+        ))
+    }
+
+    fn center(&self) -> Vec3 {
+        self.frame.origin()
+    }
+
+    fn bounds(&self) -> Aabb {
+        let pad = Vec3::new(1.0, 1.0, 1.0) * self.minor_radius;
+        let ring = Aabb::disk(self.frame.origin(), self.frame.axis(), self.major_radius);
+        Aabb::new(ring.min() - pad, ring.max() + pad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::object::Object;
+    use crate::objects::torus::Torus;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    fn torus() -> Torus {
+        Torus::new(
+            Vec3::new(0.0, 0.0, -5.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            Material::default(),
+        )
+    }
+
+    #[test]
+    fn test_torus_intersect() {
+        let torus = torus();
+        let dir = Vec3::new(0.0, 0.0, -1.0);
+        // Through the tube on the near side of the ring.
+        let t = torus
+            .distance(Vec3::default(), dir, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 2.5).abs() < 1e-4, "{}", t);
+        // Through the hole.
+        assert!(torus
+            .distance(Vec3::new(0.0, 0.7, 0.0), dir, EPS, f32::INFINITY)
+            .is_none());
+        // Down through the hole and outside the ring.
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        assert!(torus
+            .distance(Vec3::new(0.0, 5.0, -5.0), down, EPS, f32::INFINITY)
+            .is_none());
+        assert!(torus
+            .distance(Vec3::new(2.6, 5.0, -5.0), down, EPS, f32::INFINITY)
+            .is_none());
+        // Down onto the top of the tube.
+        let t = torus
+            .distance(Vec3::new(2.0, 5.0, -5.0), down, EPS, f32::INFINITY)
+            .unwrap();
+        assert!((t - 4.5).abs() < 1e-4, "{}", t);
+        // From far away.
+        let t = torus
+            .distance(
+                Vec3::new(-1000.0, 0.0, -5.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((t - 997.5).abs() < 1e-2, "{}", t);
+    }
+
+    #[test]
+    fn test_torus_norm_and_uv() {
+        let torus = torus();
+        let n = torus.norm(Vec3::new(2.0, 0.5, -5.0));
+        assert!((n - Vec3::new(0.0, 1.0, 0.0)).length() < 1e-5);
+        let n = torus.norm(Vec3::new(0.0, 0.0, -2.5));
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+
+        let (_, v) = torus.uv(Vec3::new(2.5, 0.0, -5.0));
+        assert!(v.abs() < 1e-5);
+        let bounds = torus.bounds();
+        assert!((bounds.min() - Vec3::new(-2.5, -0.5, -7.5)).length() < 1e-5);
+        assert!((bounds.max() - Vec3::new(2.5, 0.5, -2.5)).length() < 1e-5);
+    }
+}
@@ -0,0 +1,216 @@
+use crate::material::Material;
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+#[derive(Clone)]
+pub struct Triangle {
+    vertices: [Vec3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f32, f32); 3]>,
+    material: Material,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vec3; 3], material: Material) -> Self {
+        Self {
+            vertices,
+            normals: None,
+            uvs: None,
+            material,
+        }
+    }
+
+    /// A triangle whose shading normal is interpolated between the given
+    /// per-vertex normals.
+    pub fn with_normals(vertices: [Vec3; 3], normals: [Vec3; 3], material: Material) -> Self {
+        Self {
+            vertices,
+            normals: Some(normals.map(|n| n.norm())),
+            uvs: None,
+            material,
+        }
+    }
+
+    pub fn vertices(&self) -> &[Vec3; 3] {
+        &self.vertices
+    }
+
+    /// Texture coordinates of the vertices, interpolated across the
+    /// triangle in place of the barycentric weights.
+    pub fn set_uvs(&mut self, uvs: [(f32, f32); 3]) {
+        self.uvs = Some(uvs);
+    }
+}
+
+impl Object for Triangle {
+    // Möller–Trumbore
+    fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+        let (orig, dir) = (ray.orig(), ray.dir());
+        let [v0, v1, v2] = self.vertices;
+        let e1 = v1 - v0;
+        let e2 = v2 - v0;
+        let p = dir.cross(e2);
+        let det = e1 * p;
+        // Relative to the size of the triangle, so that tiny triangles are
+        // still hit while rays parallel to the plane are rejected.
+        if det.abs() <= 1e-8 * e1.length() * e2.length() * dir.length() {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+        let s = orig - v0;
+        let u = (s * p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = (dir * q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = (e2 * q) * inv_det;
+        if t <= t_min || t > t_max {
+            return None;
+        }
+
+        // `u` and `v` are the barycentric weights of the second and third
+        // vertex, they double as surface coordinates without vertex UVs.
+        let uv = match self.uvs {
+            Some([uv0, uv1, uv2]) => {
+                let w = 1.0 - u - v;
+                (
+                    uv0.0 * w + uv1.0 * u + uv2.0 * v,
+                    uv0.1 * w + uv1.1 * u + uv2.1 * v,
+                )
+            }
+            None => (u, v),
+        };
+        let mut hit = Hit::new(
+            dir,
+            t,
+            orig + dir * t,
+            e1.cross(e2).norm(),
+            uv,
+            &self.material,
+        );
+        if let Some([n0, n1, n2]) = self.normals {
+            hit.shading_normal = (n0 * (1.0 - u - v) + n1 * u + n2 * v).norm();
+        }
+        Some(hit)
+    }
+
+    fn center(&self) -> Vec3 {
+        let [v0, v1, v2] = self.vertices;
+        (v0 + v1 + v2) * (1.0 / 3.0)
+    }
+
+    fn bounds(&self) -> Aabb {
+        let [v0, v1, v2] = self.vertices;
+        Aabb::new(v0.min(v1).min(v2), v0.max(v1).max(v2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::object::Object;
+    use crate::objects::triangle::Triangle;
+    use crate::ray::Ray;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+
+    fn triangle() -> Triangle {
+        Triangle::new(
+            [
+                Vec3::new(-1.0, -1.0, -5.0),
+                Vec3::new(1.0, -1.0, -5.0),
+                Vec3::new(0.0, 1.0, -5.0),
+            ],
+            Material::default(),
+        )
+    }
+
+    #[test]
+    fn test_triangle_hit() {
+        let triangle = triangle();
+        let hit = triangle
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 5.0).abs() < 1e-6);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), hit.normal);
+        assert_eq!(hit.normal, hit.shading_normal);
+        assert!(hit.front_face);
+        assert_eq!((0.25, 0.5), hit.uv);
+    }
+
+    #[test]
+    fn test_triangle_miss() {
+        let t = triangle();
+        let miss = |dir: Vec3| {
+            t.intersect(&Ray::new(Vec3::default(), dir), EPS, f32::INFINITY)
+                .is_none()
+        };
+        assert!(miss(Vec3::new(0.0, 0.0, 1.0)));
+        assert!(miss(Vec3::new(1.0, 0.0, 0.0)));
+        assert!(miss(Vec3::new(0.5, 0.9, -5.0).norm()));
+        assert!(t
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                4.0
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_triangle_smooth_normal() {
+        let t = Triangle::with_normals(
+            *triangle().vertices(),
+            [
+                Vec3::new(-1.0, 0.0, 1.0),
+                Vec3::new(1.0, 0.0, 1.0),
+                Vec3::new(0.0, 0.0, 1.0),
+            ],
+            Material::default(),
+        );
+        let shading_normal = |orig: Vec3| {
+            t.intersect(
+                &Ray::new(orig, Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap()
+            .shading_normal
+        };
+        let n = shading_normal(Vec3::new(0.0, -1.0, 0.0));
+        assert!((n - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-6);
+        let n = shading_normal(Vec3::new(-0.999, -0.999, 0.0));
+        assert!((n - Vec3::new(-1.0, 0.0, 1.0).norm()).length() < 1e-3);
+    }
+
+    #[test]
+    fn test_small_triangle_hit() {
+        let t = Triangle::new(
+            [
+                Vec3::new(0.0, 0.0, -1.0),
+                Vec3::new(2e-4, 0.0, -1.0),
+                Vec3::new(0.0, 2e-4, -1.0),
+            ],
+            Material::default(),
+        );
+        let hit = t
+            .intersect(
+                &Ray::new(Vec3::new(5e-5, 5e-5, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        assert!((hit.t - 1.0).abs() < 1e-6);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), hit.normal);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::vec3::Vec3;
+
+/// A half-line from `orig` along the unit vector `dir`, sent at `time`
+/// within the shutter interval of the camera. Moving objects are hit where
+/// they are at that time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    orig: Vec3,
+    dir: Vec3,
+    time: f32,
+}
+
+impl Ray {
+    /// A ray at time 0.
+    pub fn new(orig: Vec3, dir: Vec3) -> Self {
+        Self::at_time(orig, dir, 0.0)
+    }
+
+    pub fn at_time(orig: Vec3, dir: Vec3, time: f32) -> Self {
+        Self { orig, dir, time }
+    }
+
+    pub fn orig(&self) -> Vec3 {
+        self.orig
+    }
+
+    pub fn dir(&self) -> Vec3 {
+        self.dir
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// The point at distance `t` along the ray.
+    pub fn point(&self, t: f32) -> Vec3 {
+        self.orig + self.dir * t
+    }
+
+    /// A ray sent at the same time, e.g. a reflection or shadow ray.
+    pub fn spawn(&self, orig: Vec3, dir: Vec3) -> Self {
+        Self::at_time(orig, dir, self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ray::Ray;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_ray() {
+        let ray = Ray::at_time(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0), 0.5);
+        assert_eq!(Vec3::new(1.0, 0.0, -2.0), ray.point(2.0));
+        let reflected = ray.spawn(ray.point(2.0), Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(0.5, reflected.time());
+        assert_eq!(Vec3::new(1.0, 0.0, -2.0), reflected.orig());
+        assert_eq!(
+            0.0,
+            Ray::new(Vec3::default(), Vec3::new(1.0, 0.0, 0.0)).time()
+        );
+    }
+}
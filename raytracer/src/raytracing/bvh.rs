@@ -0,0 +1,407 @@
+use crate::objects::aabb::Aabb;
+use crate::objects::object::{Hit, Object};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+// Costs of a node visit and of an object intersection test for the surface
+// area heuristic, only their ratio matters.
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+const MAX_LEAF_SIZE: usize = 4;
+// Below this depth nodes are split at the object median instead, which keeps
+// the whole tree shallow enough for the fixed size traversal stack.
+const MAX_SAH_DEPTH: usize = 32;
+const STACK_SIZE: usize = 64;
+
+enum NodeKind {
+    Leaf { start: usize, count: usize },
+    Interior { axis: usize, right: usize },
+}
+
+struct Node {
+    bounds: Aabb,
+    kind: NodeKind,
+}
+
+struct Primitive {
+    index: usize,
+    bounds: Aabb,
+    centroid: Vec3,
+}
+
+/// Bounding volume hierarchy over the objects of a scene, built with the
+/// surface area heuristic. The left child of an interior node directly
+/// follows its parent in `nodes`. Objects without finite bounds, such as
+/// infinite planes, are kept out of the tree and tested on every ray.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(objects: &[Arc<dyn Object + Sync + Send>]) -> Self {
+        let mut unbounded = Vec::new();
+        let mut primitives: Vec<Primitive> = objects
+            .iter()
+            .enumerate()
+            .filter_map(|(index, o)| {
+                let bounds = o.bounds();
+                if !bounds.is_finite() {
+                    unbounded.push(index);
+                    return None;
+                }
+                Some(Primitive {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                })
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * primitives.len()),
+            indices: Vec::with_capacity(primitives.len()),
+            unbounded,
+        };
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives, 0);
+        }
+        bvh
+    }
+
+    fn build(&mut self, primitives: &mut [Primitive], depth: usize) -> usize {
+        let bounds = primitives
+            .iter()
+            .fold(Aabb::empty(), |b, p| b.union(&p.bounds));
+        let node = self.nodes.len();
+        let count = primitives.len();
+
+        let split = if count <= 1 {
+            None
+        } else if depth < MAX_SAH_DEPTH {
+            Self::find_split(primitives, &bounds)
+        } else {
+            Some((bounds.largest_axis(), count / 2))
+        };
+
+        match split {
+            Some((axis, split)) => {
+                sort_by_axis(primitives, axis);
+                self.nodes.push(Node {
+                    bounds,
+                    kind: NodeKind::Interior { axis, right: 0 },
+                });
+                let (left, right) = primitives.split_at_mut(split);
+                self.build(left, depth + 1);
+                let right = self.build(right, depth + 1);
+                self.nodes[node].kind = NodeKind::Interior { axis, right };
+            }
+            None => {
+                self.nodes.push(Node {
+                    bounds,
+                    kind: NodeKind::Leaf {
+                        start: self.indices.len(),
+                        count,
+                    },
+                });
+                self.indices.extend(primitives.iter().map(|p| p.index));
+            }
+        }
+
+        node
+    }
+
+    // Sweeps every axis over the primitives sorted by centroid and returns the
+    // axis and the size of the left half of the cheapest split, or `None` if a
+    // leaf is cheaper.
+    fn find_split(primitives: &mut [Primitive], bounds: &Aabb) -> Option<(usize, usize)> {
+        let count = primitives.len();
+        let centroid_bounds = primitives
+            .iter()
+            .fold(Aabb::empty(), |b, p| b.grow(p.centroid));
+        let parent_area = bounds.surface_area().max(f32::MIN_POSITIVE);
+
+        let mut best: Option<(f32, usize, usize)> = None;
+        let mut right_areas = vec![0.0; count];
+        for axis in 0..3 {
+            if centroid_bounds.max()[axis] <= centroid_bounds.min()[axis] {
+                continue;
+            }
+            sort_by_axis(primitives, axis);
+
+            let mut right = Aabb::empty();
+            for i in (1..count).rev() {
+                right = right.union(&primitives[i].bounds);
+                right_areas[i] = right.surface_area();
+            }
+
+            let mut left = Aabb::empty();
+            for i in 1..count {
+                left = left.union(&primitives[i - 1].bounds);
+                let cost = TRAVERSAL_COST
+                    + INTERSECTION_COST
+                        * (left.surface_area() * i as f32 + right_areas[i] * (count - i) as f32)
+                        / parent_area;
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, i));
+                }
+            }
+        }
+
+        let (cost, axis, split) = best?;
+        if count <= MAX_LEAF_SIZE && INTERSECTION_COST * count as f32 <= cost {
+            return None;
+        }
+        Some((axis, split))
+    }
+
+    /// Returns the index and hit of the nearest object hit by the ray within
+    /// `(t_min, t_max]`. Of several objects at the same distance the one that
+    /// comes last in `objects` wins, exactly as in a linear scan.
+    pub fn intersect<'o>(
+        &self,
+        objects: &'o [Arc<dyn Object + Sync + Send>],
+        ray: &Ray,
+        t_min: f32,
+        t_max: f32,
+    ) -> Option<(usize, Hit<'o>)> {
+        let mut nearest: Option<(usize, Hit<'o>)> = None;
+        let test = |index: usize, nearest: &mut Option<(usize, Hit<'o>)>| {
+            let t_max = nearest.as_ref().map_or(t_max, |(_, hit)| hit.t);
+            if let Some(hit) = objects[index].intersect(ray, t_min, t_max) {
+                let closer = nearest.as_ref().is_none_or(|(best, best_hit)| {
+                    hit.t < best_hit.t || (hit.t == best_hit.t && index > *best)
+                });
+                if closer {
+                    *nearest = Some((index, hit));
+                }
+            }
+        };
+
+        // Unbounded objects go first so that they can shrink `t_max`.
+        for &index in &self.unbounded {
+            test(index, &mut nearest);
+        }
+        if self.nodes.is_empty() {
+            return nearest;
+        }
+
+        let (orig, dir) = (ray.orig(), ray.dir());
+        let inv_dir = Vec3::new(1.0 / dir.x(), 1.0 / dir.y(), 1.0 / dir.z());
+        let mut stack = [0; STACK_SIZE];
+        let mut size = 1;
+
+        while size > 0 {
+            size -= 1;
+            let i = stack[size];
+            let node = &self.nodes[i];
+            let t_max = nearest.as_ref().map_or(t_max, |(_, hit)| hit.t);
+            if node.bounds.hit(orig, inv_dir, t_max).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { start, count } => {
+                    for &index in &self.indices[start..start + count] {
+                        test(index, &mut nearest);
+                    }
+                }
+                NodeKind::Interior { axis, right } => {
+                    // Visit the nearer child first so that it can shrink `t_max`.
+                    let (near, far) = if dir[axis] < 0.0 {
+                        (right, i + 1)
+                    } else {
+                        (i + 1, right)
+                    };
+                    stack[size] = far;
+                    stack[size + 1] = near;
+                    size += 2;
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+fn sort_by_axis(primitives: &mut [Primitive], axis: usize) {
+    primitives.sort_by(|a, b| {
+        a.centroid[axis]
+            .total_cmp(&b.centroid[axis])
+            .then(a.index.cmp(&b.index))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Material;
+    use crate::objects::aabb::Aabb;
+    use crate::objects::object::{Hit, Object};
+    use crate::objects::plane::Plane;
+    use crate::objects::sphere::Sphere;
+    use crate::ray::Ray;
+    use crate::raytracing::bvh::Bvh;
+    use crate::raytracing::util::EPS;
+    use crate::vec3::Vec3;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct Counting {
+        sphere: Sphere,
+        tests: Arc<AtomicUsize>,
+    }
+
+    impl Object for Counting {
+        fn intersect(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<Hit<'_>> {
+            self.tests.fetch_add(1, Ordering::Relaxed);
+            self.sphere.intersect(ray, t_min, t_max)
+        }
+
+        fn center(&self) -> Vec3 {
+            self.sphere.center()
+        }
+
+        fn bounds(&self) -> Aabb {
+            self.sphere.bounds()
+        }
+    }
+
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self, lo: f32, hi: f32) -> f32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            lo + (hi - lo) * ((self.0 >> 40) as f32 / (1u64 << 24) as f32)
+        }
+
+        fn vec3(&mut self, lo: f32, hi: f32) -> Vec3 {
+            Vec3::new(self.next(lo, hi), self.next(lo, hi), self.next(lo, hi))
+        }
+    }
+
+    fn linear(
+        objects: &[Arc<dyn Object + Sync + Send>],
+        orig: Vec3,
+        dir: Vec3,
+    ) -> Option<(usize, f32)> {
+        let mut nearest = None;
+        let mut nearest_dist = f32::MAX;
+        for (index, o) in objects.iter().enumerate() {
+            match o.intersect(&Ray::new(orig, dir), EPS, f32::MAX) {
+                Some(hit) if hit.t <= nearest_dist => {
+                    nearest_dist = hit.t;
+                    nearest = Some((index, hit.t));
+                }
+                _ => {}
+            }
+        }
+        nearest
+    }
+
+    fn traverse(
+        bvh: &Bvh,
+        objects: &[Arc<dyn Object + Sync + Send>],
+        orig: Vec3,
+        dir: Vec3,
+    ) -> Option<(usize, f32)> {
+        bvh.intersect(objects, &Ray::new(orig, dir), EPS, f32::MAX)
+            .map(|(index, hit)| (index, hit.t))
+    }
+
+    fn spheres(
+        random: &mut Random,
+        count: usize,
+        tests: &Arc<AtomicUsize>,
+    ) -> Vec<Arc<dyn Object + Sync + Send>> {
+        (0..count)
+            .map(|_| {
+                let sphere = Sphere::new(
+                    random.vec3(-50.0, 50.0),
+                    random.next(0.1, 1.0),
+                    Material::default(),
+                );
+                Arc::new(Counting {
+                    sphere,
+                    tests: tests.clone(),
+                }) as Arc<dyn Object + Sync + Send>
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_bvh_matches_linear_scan() {
+        let mut random = Random(0x2545f4914f6cdd1d);
+        let tests = Arc::new(AtomicUsize::new(0));
+        let mut objects = spheres(&mut random, 500, &tests);
+        objects.push(Arc::new(Plane::new(
+            Vec3::new(0.0, -5.0, -15.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            Material::default(),
+        )));
+        objects.push(Arc::new(Plane::infinite(
+            Vec3::new(0.0, 0.0, -55.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Material::default(),
+        )));
+        // Two identical spheres: the later one has to win, as in the linear scan.
+        let twin = Sphere::new(Vec3::new(0.0, 80.0, 0.0), 2.0, Material::default());
+        objects.push(Arc::new(twin.clone()));
+        objects.push(Arc::new(twin));
+
+        let bvh = Bvh::new(&objects);
+        let (orig, dir) = (Vec3::new(0.0, 80.0, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(Some(503), linear(&objects, orig, dir).map(|(i, _)| i));
+        assert_eq!(
+            linear(&objects, orig, dir),
+            traverse(&bvh, &objects, orig, dir)
+        );
+
+        for _ in 0..2000 {
+            let orig = random.vec3(-60.0, 60.0);
+            let dir = random.vec3(-1.0, 1.0).norm();
+            assert_eq!(
+                linear(&objects, orig, dir),
+                traverse(&bvh, &objects, orig, dir)
+            );
+        }
+    }
+
+    #[test]
+    fn test_bvh_reduces_intersection_tests() {
+        let mut random = Random(0x9e3779b97f4a7c15);
+        let tests = Arc::new(AtomicUsize::new(0));
+        let objects = spheres(&mut random, 10_000, &tests);
+        let bvh = Bvh::new(&objects);
+
+        let rays: Vec<(Vec3, Vec3)> = (0..1000)
+            .map(|_| (random.vec3(-60.0, 60.0), random.vec3(-1.0, 1.0).norm()))
+            .collect();
+
+        tests.store(0, Ordering::Relaxed);
+        let expected: Vec<_> = rays
+            .iter()
+            .map(|&(orig, dir)| linear(&objects, orig, dir))
+            .collect();
+        let linear_tests = tests.swap(0, Ordering::Relaxed);
+
+        let actual: Vec<_> = rays
+            .iter()
+            .map(|&(orig, dir)| traverse(&bvh, &objects, orig, dir))
+            .collect();
+        let bvh_tests = tests.load(Ordering::Relaxed);
+
+        assert_eq!(expected, actual);
+        assert_eq!(10_000 * 1000, linear_tests);
+        assert!(
+            bvh_tests * 50 < linear_tests,
+            "bvh: {} intersection tests, linear scan: {}",
+            bvh_tests,
+            linear_tests
+        );
+    }
+}
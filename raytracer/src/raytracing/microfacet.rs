@@ -0,0 +1,296 @@
+use crate::material::Pbr;
+use crate::raytracing::physics::{fresnel, reflect, refract};
+use crate::raytracing::sampling::{cosine_hemisphere, ggx_visible_normal};
+use crate::vec3::Vec3;
+use rand::Rng;
+use std::f32::consts::PI;
+
+// Smoother microfacets are treated as this rough to keep the distribution
+// finite, which is indistinguishable from a mirror.
+const MIN_ALPHA: f32 = 1e-3;
+
+// A sampled direction light arrives from, with its weight.
+type Sample = (Vec3, Vec3);
+
+/// The metallic-roughness model at one surface point, seen from `wo`.
+///
+/// The opaque part reflects light off GGX microfacets with the Schlick
+/// Fresnel term, whose reflectance at normal incidence goes from that of
+/// the refractive index to the base color with `metallic`. Light that the
+/// microfacets of a dielectric do not reflect reaches a Lambert base. The
+/// `transmission` share of the dielectric instead refracts that light
+/// through the microfacets, with the exact Fresnel term.
+pub(crate) struct Bsdf {
+    n: Vec3,
+    wo: Vec3,
+    cos_o: f32,
+    alpha: f32,
+    f0: Vec3,
+    diffuse: Vec3,
+    base: Vec3,
+    transmission: f32,
+    eta_t: f32,
+    eta_i: f32,
+}
+
+impl Bsdf {
+    /// `n` is the shading normal on the side of `wo`, the unit vector towards
+    /// the viewer, `front_face` tells whether that side is the outside.
+    pub(crate) fn new(
+        pbr: &Pbr,
+        base: Vec3,
+        ior: f32,
+        n: Vec3,
+        wo: Vec3,
+        front_face: bool,
+    ) -> Self {
+        let cos_o = (wo * n).max(1e-4);
+        let metallic = pbr.metallic;
+        let dielectric = ((ior - 1.0) / (ior + 1.0)).powi(2);
+        let f0 = Vec3::new(dielectric, dielectric, dielectric) * (1.0 - metallic) + base * metallic;
+        // The Lambert base gets what the microfacets of the dielectric do not
+        // reflect towards the viewer.
+        let diffuse = base * ((1.0 - metallic) * (1.0 - schlick(dielectric, cos_o)));
+        let (eta_t, eta_i) = if front_face { (ior, 1.0) } else { (1.0, ior) };
+        Self {
+            n,
+            wo,
+            cos_o,
+            alpha: (pbr.roughness * pbr.roughness).max(MIN_ALPHA),
+            f0,
+            diffuse,
+            base,
+            transmission: pbr.transmission * (1.0 - metallic),
+            eta_t,
+            eta_i,
+        }
+    }
+
+    /// Share of the light arriving from `wi` that is reflected towards the
+    /// viewer, per solid angle and without the cosine at `wi`. Light through
+    /// the surface is left out, only sampled rays find it.
+    pub(crate) fn eval(&self, wi: Vec3) -> Vec3 {
+        let cos_i = wi * self.n;
+        if cos_i <= 0.0 {
+            return Vec3::default();
+        }
+        let h = (self.wo + wi).norm();
+        let specular = ggx(self.alpha, h * self.n) * smith(self.alpha, self.cos_o, cos_i)
+            / (4.0 * self.cos_o * cos_i);
+        let opaque = schlick_color(self.f0, self.wo * h) * specular + self.diffuse * (1.0 / PI);
+        let dielectric = specular * fresnel(-self.wo, h, self.eta_t, self.eta_i);
+        opaque * (1.0 - self.transmission)
+            + Vec3::new(dielectric, dielectric, dielectric) * self.transmission
+    }
+
+    /// Share of the light reflected diffusely, the albedo of the Lambert base.
+    pub(crate) fn diffuse(&self) -> Vec3 {
+        self.diffuse * (1.0 - self.transmission)
+    }
+
+    /// Samples the direction the light reflected or transmitted towards the
+    /// viewer comes from, together with its weight, the BSDF times the
+    /// cosine over the density. Returns `None` for absorbed paths.
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Sample> {
+        if rng.gen::<f32>() < self.transmission {
+            let m = ggx_visible_normal(self.n, self.wo, self.alpha, rng);
+            let kr = fresnel(-self.wo, m, self.eta_t, self.eta_i);
+            if rng.gen::<f32>() < kr {
+                let wi = reflect(-self.wo, m).norm();
+                let weight = self.masking(wi)?;
+                return Some((wi, Vec3::new(weight, weight, weight)));
+            }
+            let wi = refract(-self.wo, m, self.eta_t, self.eta_i)?.norm();
+            if wi * self.n >= 0.0 {
+                return None;
+            }
+            return Some((wi, self.base * self.masking(-wi)?));
+        }
+
+        // Either lobe of the opaque part, by its share of the reflected light.
+        let specular = mean(schlick_color(self.f0, self.cos_o));
+        let diffuse = mean(self.diffuse);
+        if specular + diffuse <= 0.0 {
+            return None;
+        }
+        let p = specular / (specular + diffuse);
+        if rng.gen::<f32>() < p {
+            let m = ggx_visible_normal(self.n, self.wo, self.alpha, rng);
+            let wi = reflect(-self.wo, m).norm();
+            let weight = self.masking(wi)? / p;
+            Some((wi, schlick_color(self.f0, self.wo * m) * weight))
+        } else {
+            let wi = cosine_hemisphere(self.n, rng);
+            Some((wi, self.diffuse * (1.0 / (1.0 - p))))
+        }
+    }
+
+    /// The mirror-like part for a tracer that follows single rays: the light
+    /// reflected and refracted around one sampled microfacet normal, as
+    /// directions with their weights. Sums up to all non-diffuse light.
+    pub(crate) fn specular<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> (Option<Sample>, Option<Sample>) {
+        let m = ggx_visible_normal(self.n, self.wo, self.alpha, rng);
+        let kr = fresnel(-self.wo, m, self.eta_t, self.eta_i);
+        let wi = reflect(-self.wo, m).norm();
+        let reflected = self.masking(wi).map(|weight| {
+            let color = schlick_color(self.f0, self.wo * m) * (1.0 - self.transmission)
+                + Vec3::new(kr, kr, kr) * self.transmission;
+            (wi, color * weight)
+        });
+        let refracted = if self.transmission > 0.0 && kr < 1.0 {
+            refract(-self.wo, m, self.eta_t, self.eta_i)
+                .map(|wi| wi.norm())
+                .filter(|&wi| wi * self.n < 0.0)
+                .and_then(|wi| {
+                    let weight = self.masking(-wi)? * self.transmission * (1.0 - kr);
+                    Some((wi, self.base * weight))
+                })
+        } else {
+            None
+        };
+        (reflected, refracted)
+    }
+
+    // The share of light towards `wi` around a visible microfacet normal
+    // that no other microfacet blocks, `None` if `wi` is below the surface.
+    fn masking(&self, wi: Vec3) -> Option<f32> {
+        let cos_i = wi * self.n;
+        if cos_i <= 0.0 {
+            return None;
+        }
+        Some(smith(self.alpha, self.cos_o, cos_i) / smith_g1(self.alpha, self.cos_o))
+    }
+}
+
+// The GGX distribution of microfacet normals.
+fn ggx(alpha: f32, cos_h: f32) -> f32 {
+    if cos_h <= 0.0 {
+        return 0.0;
+    }
+    let a2 = alpha * alpha;
+    let d = cos_h * cos_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+// Smith's auxiliary function of GGX for a direction at the cosine `cos`.
+fn smith_lambda(alpha: f32, cos: f32) -> f32 {
+    let cos2 = cos * cos;
+    let tan2 = (1.0 - cos2).max(0.0) / cos2;
+    ((1.0 + alpha * alpha * tan2).sqrt() - 1.0) / 2.0
+}
+
+fn smith_g1(alpha: f32, cos: f32) -> f32 {
+    1.0 / (1.0 + smith_lambda(alpha, cos))
+}
+
+// The height correlated share of microfacets both directions see.
+fn smith(alpha: f32, cos_o: f32, cos_i: f32) -> f32 {
+    1.0 / (1.0 + smith_lambda(alpha, cos_o) + smith_lambda(alpha, cos_i))
+}
+
+fn schlick(f0: f32, cos: f32) -> f32 {
+    f0 + (1.0 - f0) * (1.0 - cos.clamp(0.0, 1.0)).powi(5)
+}
+
+fn schlick_color(f0: Vec3, cos: f32) -> Vec3 {
+    Vec3::new(
+        schlick(f0.x(), cos),
+        schlick(f0.y(), cos),
+        schlick(f0.z(), cos),
+    )
+}
+
+fn mean(v: Vec3) -> f32 {
+    (v.x() + v.y() + v.z()) / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::Pbr;
+    use crate::raytracing::microfacet::Bsdf;
+    use crate::raytracing::sampling::{cosine_hemisphere, pixel_rng};
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_energy_conservation() {
+        let mut rng = pixel_rng(5, 0);
+        let n = Vec3::new(0.0, 0.0, 1.0);
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        let count = 20_000;
+        for metallic in [0.0, 1.0] {
+            for roughness in [0.1, 0.5, 1.0] {
+                for cos in [1.0f32, 0.5, 0.1] {
+                    let wo = Vec3::new((1.0 - cos * cos).sqrt(), 0.0, cos);
+                    let pbr = Pbr {
+                        metallic,
+                        roughness,
+                        transmission: 0.0,
+                    };
+                    let bsdf = Bsdf::new(&pbr, white, 1.5, n, wo, true);
+
+                    // The light reflected from the sampled directions does not
+                    // exceed the incoming light, and agrees with evaluating
+                    // the BSDF where its lobe is wide enough for uniform
+                    // estimates.
+                    let mut sampled = 0.0;
+                    let mut evaluated = 0.0;
+                    for _ in 0..count {
+                        if let Some((_, weight)) = bsdf.sample(&mut rng) {
+                            sampled += weight.y() / count as f32;
+                        }
+                        let wi = cosine_hemisphere(n, &mut rng);
+                        evaluated += bsdf.eval(wi).y() * std::f32::consts::PI / count as f32;
+                    }
+                    let case = (metallic, roughness, cos, sampled, evaluated);
+                    assert!(sampled <= 1.01, "{:?}", case);
+                    // Facing the viewer, smooth metals and the dielectric with
+                    // its diffuse base lose little to the microfacets that
+                    // shadow each other.
+                    if cos >= 0.5 && (roughness < 0.5 || metallic == 0.0) {
+                        assert!(sampled > 0.9, "{:?}", case);
+                    }
+                    if roughness > 0.2 {
+                        assert!((sampled - evaluated).abs() < 0.03, "{:?}", case);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transmission() {
+        let mut rng = pixel_rng(6, 0);
+        let n = Vec3::new(0.0, 0.0, 1.0);
+        let wo = n;
+        let pbr = Pbr {
+            metallic: 0.0,
+            roughness: 0.0,
+            transmission: 1.0,
+        };
+        let glass = Bsdf::new(&pbr, Vec3::new(1.0, 1.0, 1.0), 1.5, n, wo, true);
+        let mut through = 0;
+        for _ in 0..10_000 {
+            let (wi, weight) = glass.sample(&mut rng).unwrap();
+            assert!((weight.x() - 1.0).abs() < 1e-3);
+            if wi * n < 0.0 {
+                assert!(wi * n < -0.999);
+                through += 1;
+            }
+        }
+        // Glass reflects 4% of the light at normal incidence.
+        assert!(
+            (through as f32 / 10_000.0 - 0.96).abs() < 0.01,
+            "{}",
+            through
+        );
+
+        let (reflected, refracted) = glass.specular(&mut rng);
+        let (wi, weight) = reflected.unwrap();
+        assert!(wi * n > 0.999 && (weight.x() - 0.04).abs() < 1e-3);
+        let (wi, weight) = refracted.unwrap();
+        assert!(wi * n < -0.999 && (weight.x() - 0.96).abs() < 1e-3);
+    }
+}
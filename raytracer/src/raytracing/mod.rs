@@ -1,2 +1,7 @@
+pub mod bvh;
+pub mod microfacet;
+pub mod path;
 pub mod physics;
+pub mod render;
+pub mod sampling;
 pub mod util;
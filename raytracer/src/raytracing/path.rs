@@ -0,0 +1,209 @@
+use crate::material::Model;
+use crate::ray::Ray;
+use crate::raytracing::microfacet::Bsdf;
+use crate::raytracing::physics::{
+    at_optical_depth, fresnel, mean_extinction, media_spans, medium_light, occluded, reflect,
+    refract, relative_transmittance, scene_intersect, transmittance,
+};
+use crate::raytracing::sampling::{cosine_hemisphere, henyey_greenstein_lobe, phong_lobe};
+use crate::scene::Scene;
+use crate::vec3::Vec3;
+use rand::{Rng, RngCore};
+use std::f32::consts::PI;
+
+// From this depth on paths are terminated with a probability that depends on
+// their throughput (Russian roulette), survivors are weighted up to keep the
+// estimate unbiased.
+const ROULETTE_DEPTH: i32 = 3;
+
+// Only a safety net against paths that keep a throughput of one, e.g. between
+// two perfect mirrors, every other path is ended by Russian roulette first.
+const MAX_PATH_DEPTH: i32 = 1024;
+
+/// Estimates the radiance arriving along the ray with Monte Carlo path
+/// tracing.
+///
+/// The `albedo` weights of a material are used as the probabilities to scatter
+/// into the diffuse, Phong glossy, mirror and refracted direction, where the
+/// refracted share is reflected with the Fresnel probability. Weights
+/// that sum up to more than one are normalized, the remainder is absorbed.
+/// Lights are sampled explicitly at diffuse vertices and in media, while
+/// emissive surfaces and the background only contribute when a path hits
+/// them. Media scatter paths at distances sampled by their density.
+///
+/// Materials of the metallic-roughness model sample lights at every vertex
+/// and continue in a direction sampled from their BSDF instead.
+///
+/// Path length is not limited by the maximum depth of the scene, which only
+/// applies to Whitted tracing, so the estimate stays unbiased.
+pub fn trace_path<R: Rng + RngCore>(ray: &Ray, scene: &Scene, rng: &mut R) -> Vec3 {
+    let mut radiance = Vec3::default();
+    let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+    let mut ray = *ray;
+
+    for depth in 0..=MAX_PATH_DEPTH {
+        let hit = scene_intersect(&ray, scene);
+
+        // Media on the way to the surface scatter the path at a distance
+        // sampled by their optical depth, or let it pass.
+        let spans = media_spans(&ray, hit.map_or(f32::INFINITY, |hit| hit.t), scene);
+        if !spans.is_empty() {
+            let optical = -(1.0 - rng.gen::<f32>()).ln();
+            if let Some((t, span)) = at_optical_depth(&spans, optical) {
+                let medium = span.medium;
+                throughput = throughput
+                    .mul_elem(medium.scattering())
+                    .mul_elem(relative_transmittance(&spans, t))
+                    * (1.0 / mean_extinction(&medium));
+                let point = ray.point(t);
+                let direct = medium_light(&ray, point, &medium, scene, rng);
+                radiance = radiance + throughput.mul_elem(direct);
+                // Sampling the phase function leaves the throughput as it is.
+                ray = ray.spawn(point, henyey_greenstein_lobe(ray.dir(), medium.g(), rng));
+                if !survives(&mut throughput, depth, rng) {
+                    break;
+                }
+                continue;
+            }
+            throughput = throughput.mul_elem(relative_transmittance(&spans, f32::INFINITY));
+        }
+
+        let hit = match hit {
+            Some(hit) => hit,
+            None => return radiance + throughput.mul_elem(scene.background().radiance(ray.dir())),
+        };
+        let (point, n, (u, v), material) = (hit.point, hit.shading_normal, hit.uv, hit.material);
+        let dir = ray.dir();
+        if !hit.front_face {
+            throughput = throughput.mul_elem(material.transmittance(hit.t));
+        }
+        radiance = radiance + throughput.mul_elem(material.emission());
+
+        let facing = if hit.front_face { n } else { -n };
+        if let Model::Pbr(pbr) = material.model() {
+            let base = material.diffuse_color_at(u, v, point);
+            let ior = material.refractive_index();
+            let bsdf = Bsdf::new(&pbr, base, ior, facing, -dir, hit.front_face);
+            let direct = bsdf_light(&ray, point, facing, &bsdf, scene, rng);
+            radiance = radiance + throughput.mul_elem(direct);
+            let (next, weight) = match bsdf.sample(rng) {
+                Some(sampled) => sampled,
+                None => break,
+            };
+            throughput = throughput.mul_elem(weight);
+            ray = ray.spawn(point, next);
+            if !survives(&mut throughput, depth, rng) {
+                break;
+            }
+            continue;
+        }
+
+        let albedo = material.albedo().map(|weight| weight.max(0.0));
+        let mut pick = rng.gen::<f32>() * albedo.iter().sum::<f32>().max(1.0);
+        let lobe = albedo.iter().position(|&weight| {
+            pick -= weight;
+            pick < 0.0
+        });
+
+        let next = match lobe {
+            Some(0) => {
+                let diffuse = material.diffuse_color_at(u, v, point);
+                let direct = direct_light(&ray, point, facing, scene, rng);
+                radiance = radiance + throughput.mul_elem(diffuse).mul_elem(direct);
+                throughput = throughput.mul_elem(diffuse);
+                cosine_hemisphere(facing, rng)
+            }
+            Some(1) => {
+                let exponent = material.specular_exponent();
+                let glossy = phong_lobe(reflect(dir, n).norm(), exponent, rng);
+                let cos = glossy * facing;
+                if cos <= 0.0 {
+                    break;
+                }
+                throughput = throughput * ((exponent + 2.0) / (exponent + 1.0) * cos);
+                glossy
+            }
+            Some(2) => reflect(dir, n).norm(),
+            Some(_) => {
+                let ior = material.refractive_index();
+                let kr = fresnel(dir, n, ior, 1.0);
+                match refract(dir, n, ior, 1.0) {
+                    Some(refracted) if rng.gen::<f32>() >= kr => refracted.norm(),
+                    _ => reflect(dir, n).norm(),
+                }
+            }
+            None => break,
+        };
+        ray = ray.spawn(point, next);
+        if !survives(&mut throughput, depth, rng) {
+            break;
+        }
+    }
+
+    radiance
+}
+
+// Russian roulette after the vertex at `depth`, returns whether the path
+// goes on.
+fn survives<R: Rng>(throughput: &mut Vec3, depth: i32, rng: &mut R) -> bool {
+    if depth < ROULETTE_DEPTH {
+        return true;
+    }
+    let survival = throughput
+        .x()
+        .max(throughput.y())
+        .max(throughput.z())
+        .min(1.0);
+    if rng.gen::<f32>() >= survival {
+        return false;
+    }
+    *throughput = *throughput * (1.0 / survival);
+    true
+}
+
+// Light from the light sources that `bsdf` reflects towards the ray origin,
+// in the units of `direct_light`.
+fn bsdf_light(
+    ray: &Ray,
+    point: Vec3,
+    n: Vec3,
+    bsdf: &Bsdf,
+    scene: &Scene,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    let mut sum = Vec3::default();
+    for light in scene.lights() {
+        for sample in light.samples(point, rng) {
+            let shadow = ray.spawn(point, sample.direction);
+            if !occluded(&shadow, sample.distance, scene) {
+                sum = sum
+                    + bsdf
+                        .eval(sample.direction)
+                        .mul_elem(sample.intensity)
+                        .mul_elem(transmittance(&shadow, sample.distance, scene))
+                        * (PI * f32::max(0.0, sample.direction * n));
+            }
+        }
+    }
+    sum
+}
+
+// Light arriving from the light sources, in the units of the Whitted tracer:
+// an unoccluded sample contributes its intensity times the cosine of its
+// incident angle. Shadow rays are sent at the time of `ray`.
+fn direct_light(ray: &Ray, point: Vec3, n: Vec3, scene: &Scene, rng: &mut dyn RngCore) -> Vec3 {
+    let mut sum = Vec3::default();
+    for light in scene.lights() {
+        for sample in light.samples(point, rng) {
+            let shadow = ray.spawn(point, sample.direction);
+            if !occluded(&shadow, sample.distance, scene) {
+                sum = sum
+                    + sample
+                        .intensity
+                        .mul_elem(transmittance(&shadow, sample.distance, scene))
+                        * f32::max(0.0, sample.direction * n);
+            }
+        }
+    }
+    sum
+}
@@ -1,84 +1,569 @@
-use crate::material::Material;
-use crate::objects::object::Object;
-use crate::raytracing::util::{CLOSEST_VIEW_DISTANCE, MAX_REFLECTION_DEPTH};
+use crate::material::{Model, Pbr};
+use crate::media::medium::Medium;
+use crate::objects::object::Hit;
+use crate::ray::Ray;
+use crate::raytracing::microfacet::Bsdf;
+use crate::raytracing::sampling::{cosine_hemisphere, henyey_greenstein_lobe};
+use crate::raytracing::util::{CLOSEST_VIEW_DISTANCE, EPS};
+use crate::scene::Scene;
 use crate::vec3::Vec3;
-use std::sync::Arc;
+use rand::{Rng, RngCore};
+use std::f32::consts::PI;
 
-fn reflect(i: Vec3, n: Vec3) -> Vec3 {
+pub(crate) fn reflect(i: Vec3, n: Vec3) -> Vec3 {
     i - n * 2.0 * (i * n)
 }
 
-fn refract(i: Vec3, n: Vec3, eta_t: f32, eta_i: f32) -> Vec3 {
-    let cos = -(f32::max(-1.0, f32::min(1.0, i * n)));
+/// Refracts `i` at a surface with normal `n` between a medium with index
+/// `eta_i` on the side `n` points to and one with index `eta_t` behind it.
+/// Returns `None` on total internal reflection.
+pub(crate) fn refract(i: Vec3, n: Vec3, eta_t: f32, eta_i: f32) -> Option<Vec3> {
+    let cos = -(i * n).clamp(-1.0, 1.0);
     if cos < 0.0 {
         return refract(i, -n, eta_i, eta_t);
     }
     let eta = eta_i / eta_t;
     let k = 1.0 - eta.powi(2) * (1.0 - cos.powi(2));
     if k.is_sign_negative() {
-        Vec3::new(1.0, 0.0, 0.0)
+        None
     } else {
-        i * eta + n * (eta * cos - k.sqrt())
+        Some(i * eta + n * (eta * cos - k.sqrt()))
     }
 }
 
-fn scene_intersect(
-    orig: Vec3,
-    dir: Vec3,
-    objects: &Vec<Arc<dyn Object + Sync + Send>>,
-) -> (bool, Vec3, Vec3, Material) {
-    let mut pt: Vec3 = Vec3::default();
-    let mut n: Vec3 = Vec3::default();
-    let mut material = Material::default();
+/// Fraction of unpolarized light that a dielectric reflects, from the exact
+/// Fresnel equations. Takes the same arguments as `refract` and returns 1
+/// on total internal reflection.
+pub(crate) fn fresnel(i: Vec3, n: Vec3, eta_t: f32, eta_i: f32) -> f32 {
+    let cos_i = -(i * n).clamp(-1.0, 1.0);
+    if cos_i < 0.0 {
+        return fresnel(i, -n, eta_i, eta_t);
+    }
+    let sin_t = eta_i / eta_t * (1.0 - cos_i * cos_i).max(0.0).sqrt();
+    if sin_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin_t * sin_t).max(0.0).sqrt();
+    let rs = (eta_t * cos_i - eta_i * cos_t) / (eta_t * cos_i + eta_i * cos_t);
+    let rp = (eta_i * cos_i - eta_t * cos_t) / (eta_i * cos_i + eta_t * cos_t);
+    (rs * rs + rp * rp) / 2.0
+}
+
+/// Returns the nearest hit in front of the ray origin.
+pub(crate) fn scene_intersect<'s>(ray: &Ray, scene: &'s Scene) -> Option<Hit<'s>> {
+    scene_intersect_object(ray, scene).map(|(_, hit)| hit)
+}
+
+/// Like `scene_intersect`, but also returns the index of the hit object in
+/// `scene.objects()`.
+pub(crate) fn scene_intersect_object<'s>(ray: &Ray, scene: &'s Scene) -> Option<(usize, Hit<'s>)> {
+    scene
+        .bvh()
+        .intersect(scene.objects(), ray, EPS, CLOSEST_VIEW_DISTANCE)
+}
+
+/// Returns true if an object blocks the shadow ray towards a light sample
+/// `distance` away.
+pub(crate) fn occluded(ray: &Ray, distance: f32, scene: &Scene) -> bool {
+    scene
+        .bvh()
+        .intersect(
+            scene.objects(),
+            ray,
+            EPS,
+            distance.min(CLOSEST_VIEW_DISTANCE),
+        )
+        .is_some()
+}
+
+/// A piece of a ray along which the media do not change, with the media
+/// that overlap there combined into one.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MediumSpan {
+    pub start: f32,
+    pub end: f32,
+    pub medium: Medium,
+}
+
+/// Splits the ray between its origin and `t_max` into spans of constant
+/// media, leaving out what lies in empty space. The fog ends at the view
+/// distance.
+pub(crate) fn media_spans(ray: &Ray, t_max: f32, scene: &Scene) -> Vec<MediumSpan> {
+    spans(ray, t_max, true, scene)
+}
+
+fn spans(ray: &Ray, t_max: f32, fog: bool, scene: &Scene) -> Vec<MediumSpan> {
+    if !scene.has_media() {
+        return Vec::new();
+    }
+    let t_max = t_max.min(CLOSEST_VIEW_DISTANCE);
+    let mut pieces = Vec::new();
+    if let Some(fog) = scene.fog().filter(|_| fog) {
+        pieces.push((0.0, t_max, fog));
+    }
+    for volume in scene.volumes() {
+        for (start, end) in volume.spans(ray, t_max) {
+            pieces.push((start, end, volume.medium()));
+        }
+    }
+    let span = |start, end, medium| MediumSpan { start, end, medium };
+    if pieces.len() <= 1 {
+        return pieces
+            .into_iter()
+            .map(|(start, end, medium)| span(start, end, *medium))
+            .collect();
+    }
+
+    let mut cuts: Vec<f32> = pieces
+        .iter()
+        .flat_map(|&(start, end, _)| [start, end])
+        .collect();
+    cuts.sort_by(f32::total_cmp);
+    cuts.dedup();
+    cuts.windows(2)
+        .filter_map(|pair| {
+            let middle = 0.5 * (pair[0] + pair[1]);
+            let media = pieces
+                .iter()
+                .filter(|&&(start, end, _)| start <= middle && middle < end)
+                .map(|&(_, _, medium)| medium);
+            combine(media).map(|medium| span(pair[0], pair[1], medium))
+        })
+        .collect()
+}
+
+// Overlapping media add up their coefficients. Their phase functions are
+// approximated by one with the asymmetry averaged by scattering.
+fn combine<'m>(media: impl Iterator<Item = &'m Medium>) -> Option<Medium> {
+    let mut combined: Option<Medium> = None;
+    let mut weighted_g = 0.0;
+    for medium in media {
+        weighted_g += medium.g() * mean(medium.scattering());
+        combined = Some(match combined {
+            Some(sum) => Medium::new(
+                sum.absorption() + medium.absorption(),
+                sum.scattering() + medium.scattering(),
+                0.0,
+            ),
+            None => *medium,
+        });
+    }
+    combined.map(|mut medium| {
+        let scattering = mean(medium.scattering());
+        medium.set_g(if scattering > 0.0 {
+            weighted_g / scattering
+        } else {
+            0.0
+        });
+        medium
+    })
+}
 
-    let mut nearest_dist = f32::MAX;
+fn mean(v: Vec3) -> f32 {
+    (v.x() + v.y() + v.z()) / 3.0
+}
+
+/// Fraction of light that passes the spans up to distance `t`.
+pub(crate) fn spans_transmittance(spans: &[MediumSpan], t: f32) -> Vec3 {
+    let mut transmittance = Vec3::new(1.0, 1.0, 1.0);
+    for span in spans.iter().take_while(|span| span.start < t) {
+        transmittance =
+            transmittance.mul_elem(span.medium.transmittance(span.end.min(t) - span.start));
+    }
+    transmittance
+}
+
+/// Fraction of light that passes the media on the ray between its origin
+/// and `distance`. Light from infinitely far, such as directional lights
+/// and the background, shines into the fog from outside of it and only
+/// passes the volumes.
+pub(crate) fn transmittance(ray: &Ray, distance: f32, scene: &Scene) -> Vec3 {
+    if scene.has_media() {
+        media_transmittance(ray, distance, scene)
+    } else {
+        Vec3::new(1.0, 1.0, 1.0)
+    }
+}
+
+// Kept out of line, inlined into every shadow ray it slows down scenes
+// without media.
+#[inline(never)]
+fn media_transmittance(ray: &Ray, distance: f32, scene: &Scene) -> Vec3 {
+    let spans = spans(ray, distance, distance.is_finite(), scene);
+    spans_transmittance(&spans, distance)
+}
+
+/// Extinction averaged over the color channels, by which distances in media
+/// are sampled.
+pub(crate) fn mean_extinction(medium: &Medium) -> f32 {
+    mean(medium.extinction())
+}
+
+/// Optical depth of the spans for their mean extinction.
+fn optical_depth(spans: &[MediumSpan]) -> f32 {
+    spans
+        .iter()
+        .map(|span| mean_extinction(&span.medium) * (span.end - span.start))
+        .sum()
+}
+
+/// Finds the distance at which the optical depth of the spans reaches
+/// `depth`, with the span it lies in. Taking `depth` from an exponential
+/// distribution samples distances with a density of the mean extinction
+/// times the mean transmittance.
+pub(crate) fn at_optical_depth(spans: &[MediumSpan], depth: f32) -> Option<(f32, &MediumSpan)> {
+    let mut left = depth;
+    for span in spans {
+        let sigma = mean_extinction(&span.medium);
+        let span_depth = sigma * (span.end - span.start);
+        if left < span_depth {
+            return Some((span.start + left / sigma, span));
+        }
+        left -= span_depth;
+    }
+    None
+}
+
+/// Transmittance of the spans up to distance `t` divided by the mean one
+/// that distances are sampled by, computed without the two underflowing
+/// in dense media.
+pub(crate) fn relative_transmittance(spans: &[MediumSpan], t: f32) -> Vec3 {
+    let mut exponent = Vec3::default();
+    for span in spans.iter().take_while(|span| span.start < t) {
+        let extinction = span.medium.extinction();
+        let length = span.end.min(t) - span.start;
+        exponent = exponent - (extinction - mean(extinction)) * length;
+    }
+    Vec3::new(exponent.x().exp(), exponent.y().exp(), exponent.z().exp())
+}
+
+/// Light that a medium at `point` on the ray scatters back along it, from
+/// the light sources, in the units of the light reflected by surfaces.
+/// Shadow rays pass through media.
+pub(crate) fn medium_light(
+    ray: &Ray,
+    point: Vec3,
+    medium: &Medium,
+    scene: &Scene,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    let mut sum = Vec3::default();
+    for light in scene.lights() {
+        for sample in light.samples(point, rng) {
+            let shadow = ray.spawn(point, sample.direction);
+            if occluded(&shadow, sample.distance, scene) {
+                continue;
+            }
+            // A light of intensity 1 gives a surface facing it the irradiance
+            // of pi, which reflects its full diffuse color.
+            let phase = medium.phase(sample.direction * ray.dir());
+            sum = sum
+                + sample
+                    .intensity
+                    .mul_elem(transmittance(&shadow, sample.distance, scene))
+                    * (PI * phase);
+        }
+    }
+    sum
+}
 
-    for o in objects {
-        let (intersection, d) = o.intersect(orig, dir);
-        if !intersection || d > nearest_dist {
-            continue;
+// Light scattered towards the ray origin by the media along the spans,
+// once from the light sources, gathered at `scene.medium_samples()`
+// stratified distances. With background samples the media also scatter the
+// background seen in one direction per distance.
+fn in_scattered(ray: &Ray, spans: &[MediumSpan], scene: &Scene, rng: &mut dyn RngCore) -> Vec3 {
+    let count = scene.medium_samples();
+    let total = optical_depth(spans);
+    if count == 0 || total <= 0.0 {
+        return Vec3::default();
+    }
+    // The distances are restricted to the spans, so the density is
+    // normalized by the probability to scatter within them.
+    let inside = 1.0 - (-total).exp();
+    let mut sum = Vec3::default();
+    for i in 0..count {
+        let u = (i as f32 + rng.gen::<f32>()) / count as f32;
+        let depth = -(1.0 - u * inside).ln();
+        let (t, span) = match at_optical_depth(spans, depth) {
+            Some(found) => found,
+            None => continue,
         };
-        nearest_dist = d;
-        pt = orig + dir * nearest_dist;
-        n = o.norm(pt);
-        material = o.material(pt);
+        let point = ray.point(t);
+        let medium = &span.medium;
+        let mut light = medium_light(ray, point, medium, scene, rng);
+        if scene.background_samples() > 0 {
+            let dir = henyey_greenstein_lobe(ray.dir(), medium.g(), rng);
+            let background = ray.spawn(point, dir);
+            if !occluded(&background, f32::INFINITY, scene) {
+                light = light
+                    + scene.background().radiance(dir).mul_elem(transmittance(
+                        &background,
+                        f32::INFINITY,
+                        scene,
+                    ));
+            }
+        }
+        // The transmittance to the point over the density of its distance.
+        let weight =
+            relative_transmittance(spans, t) * (inside / (mean_extinction(medium) * count as f32));
+        sum = sum + light.mul_elem(medium.scattering()).mul_elem(weight);
+    }
+    sum
+}
+
+pub fn cast_ray(ray: &Ray, scene: &Scene, depth: i32, rng: &mut dyn RngCore) -> Vec3 {
+    if depth > scene.max_depth() {
+        return scene.background().radiance(ray.dir());
+    }
+    let hit = scene_intersect(ray, scene);
+    let color = match &hit {
+        Some(hit) => shade(ray, hit, scene, depth, rng),
+        None => scene.background().radiance(ray.dir()),
+    };
+
+    // Media between the ray origin and the surface dim what lies behind
+    // them and add the light they scatter.
+    let spans = media_spans(ray, hit.map_or(f32::INFINITY, |hit| hit.t), scene);
+    if spans.is_empty() {
+        return color;
+    }
+    color.mul_elem(spans_transmittance(&spans, f32::INFINITY))
+        + in_scattered(ray, &spans, scene, rng)
+}
+
+// Light leaving the surface at `hit` towards the ray origin.
+fn shade(ray: &Ray, hit: &Hit, scene: &Scene, depth: i32, rng: &mut dyn RngCore) -> Vec3 {
+    let dir = ray.dir();
+    let (point, n, (u, v), material) = (hit.point, hit.shading_normal, hit.uv, hit.material);
+    if let Model::Pbr(pbr) = material.model() {
+        return shade_pbr(ray, hit, &pbr, scene, depth, rng);
     }
 
-    (nearest_dist < CLOSEST_VIEW_DISTANCE, pt, n, material)
+    // The refracted share `albedo[3]` is split between transmission and
+    // reflection by the Fresnel term, `albedo[2]` adds a plain mirror.
+    let ior = material.refractive_index();
+    let mut reflect_weight = material.albedo()[2];
+    let mut refract_color = Vec3::default();
+    if material.albedo()[3] > 0.0 {
+        let kr = fresnel(dir, n, ior, 1.0);
+        reflect_weight += material.albedo()[3] * kr;
+        if let Some(refract_dir) = refract(dir, n, ior, 1.0) {
+            refract_color = cast_ray(&ray.spawn(point, refract_dir.norm()), scene, depth + 1, rng)
+                * (material.albedo()[3] * (1.0 - kr));
+        }
+    }
+    let reflect_color = if reflect_weight > 0.0 {
+        cast_ray(
+            &ray.spawn(point, reflect(dir, n).norm()),
+            scene,
+            depth + 1,
+            rng,
+        ) * reflect_weight
+    } else {
+        Vec3::default()
+    };
+
+    let mut diffuse_light_intensity = Vec3::default();
+    let mut specular_light_intensity = Vec3::default();
+    for light in scene.lights() {
+        for sample in light.samples(point, rng) {
+            let shadow = ray.spawn(point, sample.direction);
+            if occluded(&shadow, sample.distance, scene) {
+                continue;
+            }
+            let intensity =
+                sample
+                    .intensity
+                    .mul_elem(transmittance(&shadow, sample.distance, scene));
+            diffuse_light_intensity =
+                diffuse_light_intensity + intensity * f32::max(0.0, sample.direction * n);
+            specular_light_intensity = specular_light_intensity
+                + intensity
+                    * f32::max(0.0, -reflect(-sample.direction, n) * dir)
+                        .powf(material.specular_exponent());
+        }
+    }
+
+    // The background lights like a light from every direction that sees it,
+    // in the units of the other lights a constant background of 1 lights
+    // an open surface with its full diffuse color.
+    let samples = scene.background_samples();
+    if samples > 0 && material.albedo()[0] > 0.0 {
+        let facing = if hit.front_face { n } else { -n };
+        let mut background = Vec3::default();
+        for _ in 0..samples {
+            let sky_dir = cosine_hemisphere(facing, rng);
+            let sky_ray = ray.spawn(point, sky_dir);
+            if !occluded(&sky_ray, f32::INFINITY, scene) {
+                background = background
+                    + scene.background().radiance(sky_dir).mul_elem(transmittance(
+                        &sky_ray,
+                        f32::INFINITY,
+                        scene,
+                    ));
+            }
+        }
+        diffuse_light_intensity = diffuse_light_intensity + background * (1.0 / samples as f32);
+    }
+
+    let color = material.emission()
+        + material
+            .diffuse_color_at(u, v, point)
+            .mul_elem(diffuse_light_intensity)
+            * material.albedo()[0]
+        + specular_light_intensity * material.albedo()[1]
+        + reflect_color
+        + refract_color;
+
+    // A ray that hits the inside of a surface travelled through the object.
+    if !hit.front_face {
+        color.mul_elem(material.transmittance(hit.t))
+    } else {
+        color
+    }
 }
 
-pub fn cast_ray(
-    orig: Vec3,
-    dir: Vec3,
-    lights: &Vec<Vec3>,
-    objects: &Vec<Arc<dyn Object + Sync + Send>>,
+// `shade` for the metallic-roughness model. Light sources are evaluated with
+// the whole BSDF, the background only lights the diffuse base, and one ray
+// each follows the reflection and the refraction of a sampled microfacet.
+fn shade_pbr(
+    ray: &Ray,
+    hit: &Hit,
+    pbr: &Pbr,
+    scene: &Scene,
     depth: i32,
+    rng: &mut dyn RngCore,
 ) -> Vec3 {
-    let (hit, point, n, material) = scene_intersect(orig, dir, objects);
-    if depth > MAX_REFLECTION_DEPTH || !hit {
-        return Vec3::new(0.2, 0.2, 0.2);
-    }
-
-    let reflect_dir = reflect(dir, n).norm();
-    let refract_dir = refract(dir, n, material.refractive_index(), 1.0).norm();
-    let reflect_color = cast_ray(point, reflect_dir, lights, objects, depth + 1);
-    let refract_color = cast_ray(point, refract_dir, lights, objects, depth + 1);
-
-    let mut diffuse_light_intensity = 0.0;
-    let mut specular_light_intensity = 0.0;
-    for light in lights {
-        let light_dir = (*light - point).norm();
-        let (hit, shadow_pt, _, _) = scene_intersect(point, light_dir, objects);
-        if hit && (shadow_pt - point).length() < (*light - point).length() {
-            continue;
+    let (point, (u, v), material) = (hit.point, hit.uv, hit.material);
+    let facing = if hit.front_face {
+        hit.shading_normal
+    } else {
+        -hit.shading_normal
+    };
+    let base = material.diffuse_color_at(u, v, point);
+    let bsdf = Bsdf::new(
+        pbr,
+        base,
+        material.refractive_index(),
+        facing,
+        -ray.dir(),
+        hit.front_face,
+    );
+
+    let mut color = material.emission();
+    for light in scene.lights() {
+        for sample in light.samples(point, rng) {
+            let shadow = ray.spawn(point, sample.direction);
+            if occluded(&shadow, sample.distance, scene) {
+                continue;
+            }
+            // Lights are in the units of the Phong model, where a light of
+            // intensity 1 gives the irradiance of pi.
+            let cos = f32::max(0.0, sample.direction * facing);
+            color = color
+                + bsdf
+                    .eval(sample.direction)
+                    .mul_elem(sample.intensity)
+                    .mul_elem(transmittance(&shadow, sample.distance, scene))
+                    * (PI * cos);
+        }
+    }
+
+    let samples = scene.background_samples();
+    let diffuse = bsdf.diffuse();
+    if samples > 0 && diffuse != Vec3::default() {
+        let mut background = Vec3::default();
+        for _ in 0..samples {
+            let sky_dir = cosine_hemisphere(facing, rng);
+            let sky_ray = ray.spawn(point, sky_dir);
+            if !occluded(&sky_ray, f32::INFINITY, scene) {
+                background = background
+                    + scene.background().radiance(sky_dir).mul_elem(transmittance(
+                        &sky_ray,
+                        f32::INFINITY,
+                        scene,
+                    ));
+            }
         }
-        diffuse_light_intensity += f32::max(0.0, light_dir * n);
-        specular_light_intensity +=
-            f32::max(0.0, -reflect(-light_dir, n) * dir).powf(material.specular_exponent());
+        color = color + diffuse.mul_elem(background) * (1.0 / samples as f32);
+    }
+
+    let (reflected, refracted) = bsdf.specular(rng);
+    for (dir, weight) in reflected.into_iter().chain(refracted) {
+        if weight != Vec3::default() {
+            color =
+                color + cast_ray(&ray.spawn(point, dir), scene, depth + 1, rng).mul_elem(weight);
+        }
+    }
+
+    if !hit.front_face {
+        color.mul_elem(material.transmittance(hit.t))
+    } else {
+        color
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ray::Ray;
+    use crate::raytracing::physics::{fresnel, media_spans, refract, transmittance};
+    use crate::scene::Scene;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_refract() {
+        let n = Vec3::new(0.0, 1.0, 0.0);
+        let i = Vec3::new(1.0, -1.0, 0.0).norm();
+        let t = refract(i, n, 1.5, 1.0).unwrap();
+        // Snell's law: sin_t = sin_i / 1.5
+        assert!((t.x() - i.x() / 1.5).abs() < 1e-6);
+        assert!(t.y() < 0.0);
+
+        // Leaving the glass at 45 degrees is past the critical angle.
+        let leaving = Vec3::new(1.0, 1.0, 0.0).norm();
+        assert_eq!(None, refract(leaving, n, 1.5, 1.0));
+        assert!(refract(Vec3::new(0.5, 1.0, 0.0).norm(), n, 1.5, 1.0).is_some());
+    }
+
+    #[test]
+    fn test_fresnel() {
+        let n = Vec3::new(0.0, 1.0, 0.0);
+        let head_on = Vec3::new(0.0, -1.0, 0.0);
+        assert!((fresnel(head_on, n, 1.5, 1.0) - 0.04).abs() < 1e-6);
+        // Symmetric at normal incidence.
+        assert!((fresnel(-head_on, n, 1.5, 1.0) - 0.04).abs() < 1e-6);
 
-    material.diffuse_color() * diffuse_light_intensity * material.albedo()[0]
-        + Vec3::new(1.0, 1.0, 1.0) * specular_light_intensity * material.albedo()[1]
-        + reflect_color * material.albedo()[2]
-        + refract_color * material.albedo()[3]
+        let grazing = Vec3::new(1.0, -0.01, 0.0).norm();
+        assert!(fresnel(grazing, n, 1.5, 1.0) > 0.9);
+
+        let inside = Vec3::new(1.0, 1.0, 0.0).norm();
+        assert_eq!(1.0, fresnel(inside, n, 1.5, 1.0));
+    }
+
+    #[test]
+    fn test_media_spans() {
+        let scene = Scene::parse(
+            "fog density=0.1 g=0.5\n\
+             material name=m\n\
+             sphere name=ball center=0,0,-5 radius=1 material=m\n\
+             volume shape=ball scattering=0.3,0.3,0.3 absorption=0.2,0,0",
+        )
+        .unwrap();
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0));
+        let spans = media_spans(&ray, 10.0, &scene);
+        assert_eq!(3, spans.len());
+        assert_eq!((0.0, 10.0), (spans[0].start, spans[2].end));
+        assert!((spans[1].start - 4.0).abs() < 1e-4 && (spans[1].end - 6.0).abs() < 1e-4);
+
+        // Inside the ball fog and smoke add up, the asymmetry of the fog is
+        // weighted by its share of the scattering.
+        let both = spans[1].medium;
+        assert!((both.extinction() - Vec3::new(0.6, 0.4, 0.4)).length() < 1e-6);
+        assert!((both.g() - 0.5 * 0.1 / 0.4).abs() < 1e-6);
+
+        let expected = (-0.1f32 * 10.0 - 0.3 * 2.0).exp();
+        assert!((transmittance(&ray, 10.0, &scene).y() - expected).abs() < 1e-4);
+        let above = Ray::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(1, media_spans(&above, 10.0, &scene).len());
+    }
 }
@@ -0,0 +1,629 @@
+use crate::camera::Camera;
+use crate::film::aov::{Aov, Aovs};
+use crate::film::denoise::Denoiser;
+use crate::film::framebuffer::Framebuffer;
+use crate::raytracing::path::trace_path;
+use crate::raytracing::physics::{cast_ray, scene_intersect_object};
+use crate::raytracing::sampling::{concentric_disk, pixel_rng, stratified_offsets};
+use crate::scene::Scene;
+use crate::vec3::Vec3;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rayon::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Algorithm that computes the color seen along a camera ray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// Recursive Whitted-style tracing with direct lighting, Phong highlights,
+    /// perfect mirrors and refraction.
+    #[default]
+    Whitted,
+    /// Unbiased Monte Carlo path tracing with diffuse interreflection and
+    /// emissive materials.
+    Path,
+}
+
+impl FromStr for Integrator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "whitted" => Ok(Integrator::Whitted),
+            "path" => Ok(Integrator::Path),
+            _ => Err(format!("unknown integrator `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for Integrator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Integrator::Whitted => write!(f, "whitted"),
+            Integrator::Path => write!(f, "path"),
+        }
+    }
+}
+
+/// Edge length of the square tiles a render is split into by default.
+pub const DEFAULT_TILE_SIZE: usize = 32;
+
+/// A rectangle of pixels that is rendered as one piece of work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Splits an image into tiles of at most `size x size` pixels, row by row
+/// from the top left.
+pub fn tiles(width: usize, height: usize, size: usize) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(size) {
+        for x in (0..width).step_by(size) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: size.min(width - x),
+                height: size.min(height - y),
+            });
+        }
+    }
+    tiles
+}
+
+/// How far a render has got, reported after every finished tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Progress {
+    /// The pass being rendered, counting from 1.
+    pub pass: usize,
+    pub passes: usize,
+    /// Tiles finished over all passes so far.
+    pub tiles_done: usize,
+    /// Tiles of all passes together.
+    pub tiles: usize,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Share of the work done, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        self.tiles_done as f32 / self.tiles.max(1) as f32
+    }
+
+    /// Time left, extrapolated from the time per tile so far. Unknown until
+    /// the first tile is done.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.tiles_done == 0 {
+            return None;
+        }
+        let left = (self.tiles - self.tiles_done) as f64 / self.tiles_done as f64;
+        Some(self.elapsed.mul_f64(left))
+    }
+}
+
+/// Stops a running render from another thread. Clones share the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Renders a scene tile by tile on the rayon thread pool. The samples of a
+/// pixel are spread over several passes, each of which covers the whole
+/// image, so that a noisy preview is available after the first pass and
+/// gets refined by the following ones.
+pub struct Renderer<'a> {
+    scene: &'a Scene,
+    camera: &'a Camera,
+    tile_size: usize,
+    passes: usize,
+    denoiser: Option<Denoiser>,
+    cancel: CancelToken,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(scene: &'a Scene, camera: &'a Camera) -> Self {
+        Self {
+            scene,
+            camera,
+            tile_size: DEFAULT_TILE_SIZE,
+            passes: 1,
+            denoiser: None,
+            cancel: CancelToken::new(),
+        }
+    }
+
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// Number of passes, at most one per sample.
+    pub fn passes(&self) -> usize {
+        self.passes.min(self.scene.samples())
+    }
+
+    /// Filter applied to the rendered image and its previews, guided by the
+    /// albedo and normal AOVs.
+    pub fn denoiser(&self) -> Option<&Denoiser> {
+        self.denoiser.as_ref()
+    }
+
+    /// A token that cancels this renderer's renders.
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Panics if `tile_size` is 0.
+    pub fn set_tile_size(&mut self, tile_size: usize) {
+        assert!(tile_size > 0, "tiles must not be empty");
+        self.tile_size = tile_size;
+    }
+
+    /// Panics if `passes` is 0.
+    pub fn set_passes(&mut self, passes: usize) {
+        assert!(passes > 0, "a render needs at least one pass");
+        self.passes = passes;
+    }
+
+    pub fn set_denoiser(&mut self, denoiser: Option<Denoiser>) {
+        self.denoiser = denoiser;
+    }
+
+    /// Renders all passes into a linear framebuffer of the scene's image
+    /// size. `progress` is called from the worker threads after every tile,
+    /// `preview` after every pass with the average of all samples so far.
+    /// Both the previews and the result go through the denoiser if there is
+    /// one.
+    ///
+    /// A cancelled render stops after the tiles in flight, drops the
+    /// unfinished pass and returns the image of the finished ones, which is
+    /// black if there are none.
+    pub fn render(
+        &self,
+        progress: impl Fn(&Progress) + Sync,
+        mut preview: impl FnMut(&Framebuffer, &Progress),
+    ) -> Framebuffer {
+        let (width, height) = (self.scene.width(), self.scene.height());
+        let tiles = tiles(width, height, self.tile_size);
+        let passes = self.passes();
+        let start = Instant::now();
+        let tiles_done = AtomicUsize::new(0);
+        let report = |pass: usize| Progress {
+            pass: pass + 1,
+            passes,
+            tiles_done: tiles_done.load(Ordering::Relaxed),
+            tiles: tiles.len() * passes,
+            elapsed: start.elapsed(),
+        };
+
+        let guides = self
+            .denoiser
+            .map(|denoiser| (denoiser, self.render_aovs(&[Aov::Albedo, Aov::Normal])));
+        let finish = |image: &Framebuffer| match &guides {
+            Some((denoiser, guides)) => denoiser.denoise(
+                image,
+                guides.get(Aov::Albedo).expect("albedo is rendered"),
+                guides.get(Aov::Normal).expect("normal is rendered"),
+            ),
+            None => image.clone(),
+        };
+
+        let mut sums = vec![Vec3::default(); width * height];
+        let mut samples_done = 0;
+        let mut image = Framebuffer::new(width, height);
+        let mut result = image.clone();
+        for pass in 0..passes {
+            let rendered: Option<Vec<(Tile, Vec<Vec3>)>> = tiles
+                .par_iter()
+                .map(|&tile| {
+                    if self.cancel.is_cancelled() {
+                        return None;
+                    }
+                    let pixels = self.render_tile(tile, pass);
+                    tiles_done.fetch_add(1, Ordering::Relaxed);
+                    progress(&report(pass));
+                    Some((tile, pixels))
+                })
+                .collect();
+            let rendered = match rendered {
+                Some(rendered) if !self.cancel.is_cancelled() => rendered,
+                _ => break,
+            };
+
+            for (tile, pixels) in rendered {
+                for (row, chunk) in pixels.chunks(tile.width).enumerate() {
+                    let start = (tile.y + row) * width + tile.x;
+                    for (sum, &pixel) in sums[start..start + tile.width].iter_mut().zip(chunk) {
+                        *sum = *sum + pixel;
+                    }
+                }
+            }
+            samples_done += self.pass_samples(pass).len();
+            let scale = 1.0 / samples_done as f32;
+            for (pixel, &sum) in image.pixels_mut().iter_mut().zip(&sums) {
+                *pixel = sum * scale;
+            }
+            result = finish(&image);
+            preview(&result, &report(pass));
+        }
+
+        result
+    }
+
+    /// Renders the given AOVs from the first hits of camera rays through the
+    /// same strata of every pixel as the color samples. Rows that are not
+    /// started before the renderer is cancelled stay empty.
+    pub fn render_aovs(&self, aovs: &[Aov]) -> Aovs {
+        let (scene, camera) = (self.scene, self.camera);
+        let (width, height) = (scene.width(), scene.height());
+        let mut images = Aovs::new(aovs, width, height);
+        let kinds: Vec<Aov> = images.buffers().iter().map(|(aov, _)| *aov).collect();
+
+        // One value per AOV for every pixel of a row.
+        let rows: Vec<Vec<Vec3>> = (0..height)
+            .into_par_iter()
+            .map(|y| {
+                let mut values = vec![Vec3::default(); width * kinds.len()];
+                if self.cancel.is_cancelled() {
+                    return values;
+                }
+                for x in 0..width {
+                    let index = y * width + x;
+                    let mut rng = pixel_rng(scene.seed(), index);
+                    let offsets = stratified_offsets(scene.samples(), &mut rng);
+                    let scale = 1.0 / offsets.len() as f32;
+                    for (i, &(dx, dy)) in offsets.iter().enumerate() {
+                        let s = (x as f32 + dx) / width as f32;
+                        let t = (y as f32 + dy) / height as f32;
+                        let lens = self.lens_sample(&mut rng);
+                        let time = self.time_sample(&mut rng);
+                        let ray = camera.ray(s, t, lens, time);
+                        let (object, hit) = match scene_intersect_object(&ray, scene) {
+                            Some(hit) => hit,
+                            None => continue,
+                        };
+                        for (k, aov) in kinds.iter().enumerate() {
+                            let value = &mut values[x * kinds.len() + k];
+                            if aov.is_filtered() {
+                                *value = *value + aov.value(&hit, object, camera) * scale;
+                            } else if i == 0 {
+                                *value = aov.value(&hit, object, camera);
+                            }
+                        }
+                    }
+                }
+                values
+            })
+            .collect();
+
+        for (k, (_, image)) in images.buffers_mut().iter_mut().enumerate() {
+            for (pixel, values) in image
+                .pixels_mut()
+                .iter_mut()
+                .zip(rows.iter().flat_map(|row| row.chunks(kinds.len())))
+            {
+                *pixel = values[k];
+            }
+        }
+        images
+    }
+
+    // Pinhole cameras and closed shutters leave the random sequence
+    // untouched.
+    fn lens_sample(&self, rng: &mut impl Rng) -> (f32, f32) {
+        if self.camera.aperture() > 0.0 {
+            concentric_disk((rng.gen(), rng.gen()))
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    fn time_sample(&self, rng: &mut impl Rng) -> f32 {
+        let (open, close) = (self.camera.shutter_open(), self.camera.shutter_close());
+        if close > open {
+            open + (close - open) * rng.gen::<f32>()
+        } else {
+            open
+        }
+    }
+
+    // The indices of the pixel samples that `pass` takes.
+    fn pass_samples(&self, pass: usize) -> std::ops::Range<usize> {
+        let (samples, passes) = (self.scene.samples(), self.passes());
+        pass * samples / passes..(pass + 1) * samples / passes
+    }
+
+    // Sums of the samples of `pass` for the pixels of `tile`, row by row.
+    fn render_tile(&self, tile: Tile, pass: usize) -> Vec<Vec3> {
+        let (scene, camera) = (self.scene, self.camera);
+        let (width, height) = (scene.width(), scene.height());
+        let mut pixels = Vec::with_capacity(tile.width * tile.height);
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                let index = y * width + x;
+                let mut rng = pixel_rng(scene.seed(), index);
+                let mut offsets = stratified_offsets(scene.samples(), &mut rng);
+                // Every pass takes a random share of the strata and continues
+                // with a random sequence of its own.
+                if self.passes() > 1 {
+                    offsets.shuffle(&mut rng);
+                    rng = pixel_rng(scene.seed() ^ ((pass as u64) << 32), index);
+                }
+
+                let mut sum = Vec3::default();
+                for &(dx, dy) in &offsets[self.pass_samples(pass)] {
+                    let s = (x as f32 + dx) / width as f32;
+                    let t = (y as f32 + dy) / height as f32;
+                    let lens = self.lens_sample(&mut rng);
+                    let time = self.time_sample(&mut rng);
+                    let ray = camera.ray(s, t, lens, time);
+                    sum = sum
+                        + match scene.integrator() {
+                            Integrator::Whitted => cast_ray(&ray, scene, 0, &mut rng),
+                            Integrator::Path => trace_path(&ray, scene, &mut rng),
+                        };
+                }
+                pixels.push(sum);
+            }
+        }
+        pixels
+    }
+}
+
+/// Renders the scene as seen by `camera` into a linear framebuffer of the
+/// scene's image size. Every pixel averages `scene.samples()` rays through
+/// jittered strata of the pixel and, if the camera has an aperture or an open
+/// shutter, random points of its lens and times between shutter open and
+/// close. The jitter is reproducible for a given `scene.seed()`.
+pub fn render(scene: &Scene, camera: &Camera) -> Framebuffer {
+    Renderer::new(scene, camera).render(|_| {}, |_, _| {})
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::film::aov::Aov;
+    use crate::film::denoise::Denoiser;
+    use crate::raytracing::render::{render, tiles, Renderer, Tile};
+    use crate::scene::Scene;
+    use crate::vec3::Vec3;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn scene() -> Scene {
+        Scene::parse(
+            "image width=37 height=21 samples=4\n\
+             material name=red diffuse=1,0,0 albedo=1,0,0,0\n\
+             sphere center=0,0,-5 radius=1.5 material=red\n\
+             light position=0,5,0",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tiles() {
+        let tiles = tiles(37, 21, 16);
+        assert_eq!(6, tiles.len());
+        assert_eq!(
+            Tile {
+                x: 32,
+                y: 16,
+                width: 5,
+                height: 5
+            },
+            tiles[5]
+        );
+        let pixels: usize = tiles.iter().map(|tile| tile.width * tile.height).sum();
+        assert_eq!(37 * 21, pixels);
+    }
+
+    #[test]
+    fn test_render_tile_size() {
+        let scene = scene();
+        let image = render(&scene, scene.camera());
+        let mut renderer = Renderer::new(&scene, scene.camera());
+        renderer.set_tile_size(5);
+        assert_eq!(image.pixels(), renderer.render(|_| {}, |_, _| {}).pixels());
+    }
+
+    #[test]
+    fn test_render_passes() {
+        let scene = scene();
+        let mut renderer = Renderer::new(&scene, scene.camera());
+        renderer.set_passes(8);
+        assert_eq!(4, renderer.passes());
+
+        let reports = AtomicUsize::new(0);
+        let mut previews = Vec::new();
+        let image = renderer.render(
+            |_| {
+                reports.fetch_add(1, Ordering::Relaxed);
+            },
+            |_, progress| previews.push((progress.pass, progress.fraction())),
+        );
+        assert_eq!(4 * 2, reports.load(Ordering::Relaxed));
+        assert_eq!(vec![(1, 0.25), (2, 0.5), (3, 0.75), (4, 1.0)], previews);
+
+        // The passes take the same strata as a single pass, the center of
+        // the sphere is lit alike.
+        let single = render(&scene, scene.camera());
+        let center = 10 * 37 + 18;
+        assert!((image.pixels()[center] - single.pixels()[center]).length() < 1e-2);
+        assert!(image.pixels()[center].x() > 0.1);
+    }
+
+    #[test]
+    fn test_render_cancel() {
+        let scene = scene();
+        let mut renderer = Renderer::new(&scene, scene.camera());
+        renderer.set_passes(4);
+        let cancel = renderer.cancel_token();
+        let mut previews = 0;
+        let image = renderer.render(
+            |progress| {
+                if progress.pass == 2 {
+                    cancel.cancel();
+                }
+            },
+            |_, _| previews += 1,
+        );
+        assert_eq!(1, previews);
+        assert!(image.pixels()[10 * 37 + 18].x() > 0.1);
+
+        let image = renderer.render(|_| {}, |_, _| previews += 1);
+        assert_eq!(1, previews);
+        assert!(image.pixels().iter().all(|pixel| pixel.length() == 0.0));
+
+        // The guides of the denoiser are not rendered either.
+        renderer.set_denoiser(Some(Denoiser::default()));
+        let image = renderer.render(|_| {}, |_, _| previews += 1);
+        assert_eq!(1, previews);
+        assert!(image.pixels().iter().all(|pixel| pixel.length() == 0.0));
+        let aovs = renderer.render_aovs(&[Aov::Albedo]);
+        let albedo = aovs.get(Aov::Albedo).unwrap();
+        assert!(albedo.pixels().iter().all(|pixel| pixel.length() == 0.0));
+    }
+
+    #[test]
+    fn test_render_aovs() {
+        let scene = scene();
+        let aovs = Renderer::new(&scene, scene.camera()).render_aovs(&[
+            Aov::Depth,
+            Aov::Normal,
+            Aov::Albedo,
+            Aov::ObjectId,
+            Aov::MaterialId,
+        ]);
+        let value = |aov: Aov, x: usize, y: usize| aovs.get(aov).unwrap().get(x, y);
+
+        // The sphere's front at the center, nothing at the corner.
+        let depth = value(Aov::Depth, 18, 10).x();
+        assert!((depth - 3.5).abs() < 0.05, "{}", depth);
+        assert!(value(Aov::Normal, 18, 10).z() > 0.9);
+        assert_eq!(Vec3::new(1.0, 0.0, 0.0), value(Aov::Albedo, 18, 10));
+        assert_eq!(Vec3::new(1.0, 1.0, 1.0), value(Aov::ObjectId, 18, 10));
+        assert_eq!(Vec3::new(1.0, 1.0, 1.0), value(Aov::MaterialId, 18, 10));
+        for aov in [Aov::Depth, Aov::ObjectId] {
+            assert_eq!(Vec3::default(), value(aov, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_render_denoised() {
+        let mut scene = scene();
+        scene.set_samples(1);
+        let noisy = render(&scene, scene.camera());
+        let mut renderer = Renderer::new(&scene, scene.camera());
+        renderer.set_denoiser(Some(Denoiser::default()));
+        let image = renderer.render(|_| {}, |_, _| {});
+
+        assert_ne!(noisy.pixels(), image.pixels());
+        // The red sphere stays red and the background untouched.
+        let center = image.get(18, 10);
+        assert!(center.x() > 0.1 && center.y() < 1e-3);
+        assert!((noisy.get(0, 0) - image.get(0, 0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn test_render_background() {
+        // A white background alone lights the convex sphere from every side
+        // it sees, giving it its full diffuse color with both integrators.
+        let source = "image width=37 height=21 integrator=whitted\n\
+                      background type=solid color=1,1,1 samples=16\n\
+                      material name=red diffuse=1,0,0 albedo=1,0,0,0\n\
+                      sphere center=0,0,-5 radius=1.5 material=red";
+        for source in [source.to_string(), source.replace("whitted", "path")] {
+            let scene = Scene::parse(&source).unwrap();
+            let image = render(&scene, scene.camera());
+            assert_eq!(Vec3::new(1.0, 1.0, 1.0), image.get(0, 0));
+            assert!((image.get(18, 10) - Vec3::new(1.0, 0.0, 0.0)).length() < 1e-4);
+        }
+
+        let mut scene = Scene::parse(source).unwrap();
+        scene.set_background_samples(0);
+        let image = render(&scene, scene.camera());
+        assert_eq!(Vec3::default(), image.get(18, 10));
+    }
+
+    #[test]
+    fn test_render_media() {
+        // A ball of absorbing smoke in front of a white background lets
+        // exp(-2) through its center, both integrators agree with Beer's law.
+        let source = "image width=45 height=45 integrator=whitted\n\
+                      background type=solid color=1,1,1\n\
+                      material name=m\n\
+                      sphere name=ball center=0,0,-5 radius=1 material=m\n\
+                      volume shape=ball absorption=1,1,1";
+        let path = source.replace("whitted", "path samples=256");
+        for (source, tolerance) in [(source.to_string(), 1e-4), (path, 0.05)] {
+            let scene = Scene::parse(&source).unwrap();
+            let image = render(&scene, scene.camera());
+            assert_eq!(Vec3::new(1.0, 1.0, 1.0), image.get(0, 0));
+            let center = image.get(22, 22).x();
+            assert!((center - (-2.0f32).exp()).abs() < tolerance, "{}", center);
+        }
+
+        // Lit fog hides a black background behind light it scatters.
+        let scene = Scene::parse(
+            "image width=9 height=9 samples=4\n\
+             background type=solid color=0,0,0\n\
+             fog density=0.5\n\
+             directional_light direction=0,-1,0",
+        )
+        .unwrap();
+        let image = render(&scene, scene.camera());
+        // A whole view distance of fog scatters a quarter of the light back.
+        assert!(
+            (image.get(0, 0).x() - 0.25).abs() < 0.01,
+            "{:?}",
+            image.get(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_render_pbr_furnace() {
+        // White balls under a uniform white sky reflect about all of its light
+        // with either integrator, and never more than it.
+        for material in [
+            "base_color=1,1,1 roughness=0.4",
+            "base_color=1,1,1 metallic=1 roughness=0",
+            "base_color=1,1,1 transmission=1 roughness=0.2",
+        ] {
+            let source = format!(
+                "image width=9 height=9 samples=64 integrator=whitted\n\
+                 background type=solid color=1,1,1 samples=4\n\
+                 material name=m type=pbr {}\n\
+                 sphere center=0,0,-5 radius=1 material=m",
+                material
+            );
+            let path = source.replace("whitted", "path");
+            for source in [source, path] {
+                let scene = Scene::parse(&source).unwrap();
+                let image = render(&scene, scene.camera());
+                let center = image.get(4, 4);
+                assert!(
+                    center.x() > 0.9 && center.x() < 1.02,
+                    "{} {:?}",
+                    source,
+                    center
+                );
+            }
+        }
+    }
+}
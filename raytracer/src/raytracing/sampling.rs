@@ -0,0 +1,247 @@
+use crate::vec3::Vec3;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+use std::f32::consts::PI;
+
+/// Creates the random generator for one pixel. Each pixel gets its own
+/// stream derived from the render seed, so the result does not depend on the
+/// order in which threads pick up pixels.
+pub fn pixel_rng(seed: u64, pixel: usize) -> Pcg32 {
+    Pcg32::seed_from_u64(seed ^ (pixel as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15))
+}
+
+/// Splits `count` samples into a grid of `columns x rows` strata that covers
+/// the pixel exactly, using the most square layout `count` allows.
+pub fn strata(count: usize) -> (usize, usize) {
+    let mut columns = (count as f64).sqrt() as usize;
+    while columns > 1 && !count.is_multiple_of(columns) {
+        columns -= 1;
+    }
+    let columns = columns.max(1);
+    (columns, count / columns)
+}
+
+/// Returns `count` jittered sub-pixel offsets in `[0, 1)^2`, one uniformly
+/// distributed point per stratum. A single sample is placed in the pixel
+/// center.
+pub fn stratified_offsets<R: Rng + ?Sized>(count: usize, rng: &mut R) -> Vec<(f32, f32)> {
+    if count == 1 {
+        return vec![(0.5, 0.5)];
+    }
+
+    let (columns, rows) = strata(count);
+    let mut offsets = Vec::with_capacity(count);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x = (column as f32 + rng.gen::<f32>()) / columns as f32;
+            let y = (row as f32 + rng.gen::<f32>()) / rows as f32;
+            offsets.push((x, y));
+        }
+    }
+    offsets
+}
+
+/// Maps a uniformly distributed point of `[0, 1)^2` onto the unit disk with
+/// the concentric mapping of Shirley and Chiu, which keeps strata compact.
+pub fn concentric_disk((x, y): (f32, f32)) -> (f32, f32) {
+    let (a, b) = (2.0 * x - 1.0, 2.0 * y - 1.0);
+    if a == 0.0 && b == 0.0 {
+        return (0.0, 0.0);
+    }
+    let (r, phi) = if a.abs() > b.abs() {
+        (a, PI / 4.0 * (b / a))
+    } else {
+        (b, PI / 2.0 - PI / 4.0 * (a / b))
+    };
+    (r * phi.cos(), r * phi.sin())
+}
+
+/// Builds two unit vectors that form an orthonormal basis together with the
+/// unit vector `n`.
+pub fn orthonormal_basis(n: Vec3) -> (Vec3, Vec3) {
+    let helper = if n.x().abs() > 0.9 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let t = helper.cross(n).norm();
+    (t, n.cross(t))
+}
+
+// Turns spherical coordinates around `axis` into a direction.
+fn around(axis: Vec3, cos_theta: f32, phi: f32) -> Vec3 {
+    let (t, b) = orthonormal_basis(axis);
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    (t * (sin_theta * phi.cos()) + b * (sin_theta * phi.sin()) + axis * cos_theta).norm()
+}
+
+/// Samples a direction in the hemisphere around `n` with a density
+/// proportional to the cosine of the angle to `n`.
+pub fn cosine_hemisphere<R: Rng + ?Sized>(n: Vec3, rng: &mut R) -> Vec3 {
+    let cos_theta = rng.gen::<f32>().sqrt();
+    around(n, cos_theta, 2.0 * PI * rng.gen::<f32>())
+}
+
+/// Samples a direction uniformly from the cone around `axis` whose opening
+/// angle has the cosine `cos_max`.
+pub fn uniform_cone<R: Rng + ?Sized>(axis: Vec3, cos_max: f32, rng: &mut R) -> Vec3 {
+    let cos_theta = 1.0 - rng.gen::<f32>() * (1.0 - cos_max);
+    around(axis, cos_theta, 2.0 * PI * rng.gen::<f32>())
+}
+
+/// Samples a direction around `axis` with a density proportional to
+/// `cos^exponent` of the angle to it, the Phong specular lobe.
+pub fn phong_lobe<R: Rng + ?Sized>(axis: Vec3, exponent: f32, rng: &mut R) -> Vec3 {
+    let cos_theta = rng.gen::<f32>().powf(1.0 / (exponent + 1.0));
+    around(axis, cos_theta, 2.0 * PI * rng.gen::<f32>())
+}
+
+/// Samples the direction in which light travelling along `dir` leaves a
+/// medium with the Henyey-Greenstein phase function of asymmetry `g`.
+pub fn henyey_greenstein_lobe<R: Rng + ?Sized>(dir: Vec3, g: f32, rng: &mut R) -> Vec3 {
+    let u = rng.gen::<f32>();
+    let cos_theta = if g.abs() < 1e-3 {
+        1.0 - 2.0 * u
+    } else {
+        let s = (1.0 - g * g) / (1.0 + g - 2.0 * g * u);
+        ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+    };
+    around(dir, cos_theta, 2.0 * PI * rng.gen::<f32>())
+}
+
+/// Samples a microfacet normal of a GGX surface with the roughness `alpha`
+/// and the macro normal `n`, among the normals that the unit vector `wo`
+/// sees, proportional to their visible area (Heitz 2018).
+pub fn ggx_visible_normal<R: Rng + ?Sized>(n: Vec3, wo: Vec3, alpha: f32, rng: &mut R) -> Vec3 {
+    let (t, b) = orthonormal_basis(n);
+    // The view direction in the frame of the hemisphere the stretched
+    // microfacets map to.
+    let v = Vec3::new(alpha * (wo * t), alpha * (wo * b), (wo * n).max(1e-6)).norm();
+    let len = (v.x() * v.x() + v.y() * v.y()).sqrt();
+    let t1 = if len > 0.0 {
+        Vec3::new(-v.y() / len, v.x() / len, 0.0)
+    } else {
+        Vec3::new(1.0, 0.0, 0.0)
+    };
+    let t2 = v.cross(t1);
+
+    // A point on the disk the hemisphere projects to, of which the half
+    // facing away from `v` is partly hidden.
+    let (p1, p2) = concentric_disk((rng.gen(), rng.gen()));
+    let s = 0.5 * (1.0 + v.z());
+    let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * p2;
+    let h = t1 * p1 + t2 * p2 + v * (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt();
+
+    let m = Vec3::new(alpha * h.x(), alpha * h.y(), h.z().max(1e-6)).norm();
+    (t * m.x() + b * m.y() + n * m.z()).norm()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raytracing::sampling::{
+        concentric_disk, cosine_hemisphere, ggx_visible_normal, henyey_greenstein_lobe,
+        orthonormal_basis, phong_lobe, pixel_rng, strata, stratified_offsets,
+    };
+    use crate::vec3::Vec3;
+    use rand::Rng;
+
+    #[test]
+    fn test_strata() {
+        assert_eq!((1, 1), strata(1));
+        assert_eq!((2, 2), strata(4));
+        assert_eq!((2, 4), strata(8));
+        assert_eq!((1, 7), strata(7));
+        assert_eq!((4, 4), strata(16));
+    }
+
+    #[test]
+    fn test_stratified_offsets() {
+        let offsets = stratified_offsets(16, &mut pixel_rng(1, 0));
+        assert_eq!(16, offsets.len());
+        for (i, (x, y)) in offsets.into_iter().enumerate() {
+            assert_eq!(i % 4, (x * 4.0) as usize);
+            assert_eq!(i / 4, (y * 4.0) as usize);
+        }
+    }
+
+    #[test]
+    fn test_pixel_rng_is_deterministic() {
+        let a: Vec<u32> = (0..4).map(|_| pixel_rng(7, 42).gen()).collect();
+        assert!(a.windows(2).all(|w| w[0] == w[1]));
+        assert_ne!(pixel_rng(7, 42).gen::<u32>(), pixel_rng(7, 43).gen::<u32>());
+        assert_ne!(pixel_rng(7, 42).gen::<u32>(), pixel_rng(8, 42).gen::<u32>());
+    }
+
+    #[test]
+    fn test_concentric_disk() {
+        assert_eq!((0.0, 0.0), concentric_disk((0.5, 0.5)));
+        let (x, y) = concentric_disk((1.0, 0.5));
+        assert!((x - 1.0).abs() < 1e-6 && y.abs() < 1e-6);
+        let (x, y) = concentric_disk((0.5, 0.0));
+        assert!(x.abs() < 1e-6 && (y + 1.0).abs() < 1e-6);
+        for (x, y) in stratified_offsets(64, &mut pixel_rng(2, 0)) {
+            let (a, b) = concentric_disk((x, y));
+            assert!(a * a + b * b <= 1.0 + 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_orthonormal_basis() {
+        for n in [
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, -2.0, 3.0).norm(),
+        ] {
+            let (t, b) = orthonormal_basis(n);
+            assert!((t * n).abs() < 1e-6 && (b * n).abs() < 1e-6 && (t * b).abs() < 1e-6);
+            assert!((t.length() - 1.0).abs() < 1e-6 && (b.length() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_hemisphere_sampling() {
+        let mut rng = pixel_rng(3, 0);
+        let n = Vec3::new(0.0, 1.0, 0.0);
+        let count = 10_000;
+        let mut mean_cos = 0.0;
+        for _ in 0..count {
+            let dir = cosine_hemisphere(n, &mut rng);
+            assert!(dir * n >= 0.0);
+            mean_cos += dir * n / count as f32;
+        }
+        // E[cos] of a cosine weighted hemisphere is 2/3.
+        assert!((mean_cos - 2.0 / 3.0).abs() < 0.01, "{}", mean_cos);
+
+        for _ in 0..100 {
+            assert!(phong_lobe(n, 1000.0, &mut rng) * n > 0.99);
+        }
+
+        // The mean cosine of the Henyey-Greenstein function is g.
+        for g in [-0.5, 0.0, 0.7] {
+            let mean_cos: f32 = (0..count)
+                .map(|_| henyey_greenstein_lobe(n, g, &mut rng) * n)
+                .sum::<f32>()
+                / count as f32;
+            assert!((mean_cos - g).abs() < 0.02, "{}", mean_cos);
+        }
+    }
+
+    #[test]
+    fn test_ggx_visible_normal() {
+        let mut rng = pixel_rng(4, 0);
+        let n = Vec3::new(0.0, 0.0, 1.0);
+        let wo = Vec3::new(1.0, 0.0, 0.2).norm();
+        let mut aligned = 0;
+        for _ in 0..1000 {
+            // Only normals the view direction sees are sampled.
+            let m = ggx_visible_normal(n, wo, 0.8, &mut rng);
+            assert!(m * n > 0.0 && m * wo > 0.0, "{:?}", m);
+            assert!((m.length() - 1.0).abs() < 1e-5);
+            if ggx_visible_normal(n, wo, 1e-3, &mut rng) * n > 0.999 {
+                aligned += 1;
+            }
+        }
+        // Nearly smooth surfaces have about all microfacets along `n`.
+        assert!(aligned >= 990, "{}", aligned);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::vec3::Vec3;
+
 pub const EPS: f32 = 0.001;
 pub const MAX_REFLECTION_DEPTH: i32 = 4;
 pub const CLOSEST_VIEW_DISTANCE: f32 = 1e3;
+pub const BACKGROUND_COLOR: Vec3 = Vec3::new(0.2, 0.2, 0.2);
@@ -0,0 +1,348 @@
+//! Scene description files.
+//!
+//! A scene is a plain text file with one directive per line. Every directive
+//! starts with its kind, followed by whitespace separated `key=value` pairs.
+//! Vectors are written as comma separated numbers without spaces (`0,-5,-15`),
+//! and everything after a `#` is a comment.
+//!
+//! ```text
+//! image width=3840 height=2160
+//! camera position=0,2,4 look_at=0,0,-12 fov=60
+//!
+//! material name=glass refractive_index=1.5 albedo=0,0.9,0.1,0.8 diffuse=0.6,0.7,0.8 specular_exponent=125
+//!
+//! sphere center=-1,-1.5,-12 radius=2 material=glass
+//! plane center=0,-5,-15 normal=0,1,0 size=10
+//! light position=-20,20,20 color=1,0.9,0.8 intensity=1.5
+//! rect_light corner=-1,5,-13 edge1=2,0,0 edge2=0,0,2 samples=16
+//! ```
+//!
+//! | directive  | keys                                                                        |
+//! |------------|-----------------------------------------------------------------------------|
+//! | `image`    | `width`, `height` (pixels, default 3840x2160), `samples` (per pixel, default 1), `seed`, `integrator` (`whitted` or `path`), `fps` (frames per second of an animation, default 24), `medium_samples` (points per ray at which the Whitted integrator lights media, default 4) |
+//! | `camera`   | `position`, `look_at`, `up` (default looking down -z from the origin), `fov` (vertical, in degrees, default 60), `aperture` (lens radius, default 0 keeps everything sharp), `focus_distance` (default the distance to `look_at`), `shutter_open`, `shutter_close` (times, default 0) |
+//! | `background` | `type` and by type: `solid`: `color`; `gradient`: `bottom`, `top` (colors straight down and up); `image`: `path` (equirectangular map, relative to the scene file, `.hdr` stays linear), `rotate` (degrees about y), `intensity`; `sky`: `sun` (direction towards it), `turbidity` (haze, 2 to 10, default 3), `intensity` (luminance straight up, default 1), `sun_intensity` (of a matching directional light, default 1, 0 for none); all: `samples` (default 0, see below) |
+//! | `fog`      | `density` (share of light taken per unit of distance), `color` (the scattered part of it, default 1,1,1), `g` (phase asymmetry, -1 to 1, default 0) |
+//! | `volume`   | `shape` (name of a closed object to fill), `absorption`, `scattering` (per unit of distance), `g` |
+//! | `texture`  | `name`, `type` and by type: `solid`: `color`; `checker`: `even`, `odd` (colors), `scale` (squares per UV unit, default 8); `noise`: `color`, `scale`, `seed`; `image`: `path` (relative to the scene file) |
+//! | `material` | `name`, `type` (`phong` or `pbr`, default `phong`), `refractive_index`, `emission`, `absorption` (per unit of distance inside), `texture` (replaces the color); `phong`: `albedo` (4 numbers), `diffuse`, `specular_exponent`; `pbr`: `base_color` (default 0.8,0.8,0.8), `metallic`, `roughness` (default 0.5), `transmission`, all between 0 and 1 |
+//! | `sphere`   | `center`, `radius`, `material`, optional transform                          |
+//! | `plane`    | `center`, `normal`, `size` (square) or `width` and `height` (rectangle) or neither (infinite), `tangent` (direction of the width, default along x), optional `material` (default an orange and blue checkerboard), optional transform |
+//! | `box`      | `min`, `max` (corners), `material`, optional transform                      |
+//! | `cylinder` | `base`, `top` (centers of the caps), `radius`, `material`, optional transform |
+//! | `cone`     | `base` (center of the cap), `apex`, `radius`, `material`, optional transform |
+//! | `disk`     | `center`, `normal`, `radius`, `material`, optional transform                |
+//! | `torus`    | `center`, `axis` (default 0,1,0), `major_radius`, `minor_radius`, `material`, optional transform |
+//! | `csg`      | `op` (`union`, `intersection` or `difference`), `left`, `right` (names of closed objects), optional transform |
+//! | `triangle` | `a`, `b`, `c` (vertices), `material`                                        |
+//! | `mesh`     | `path` (Wavefront OBJ, relative to the scene file), optional `material`, optional transform |
+//! | `light`    | `position`, `color` (default 1,1,1), `intensity` (default 1)                |
+//! | `directional_light` | `direction` (the light travels along), `color`, `intensity`        |
+//! | `spot_light` | `position`, `direction`, `angle` (outer, in degrees), `inner_angle` (default `angle`), `color`, `intensity` |
+//! | `rect_light` | `corner`, `edge1`, `edge2` (shines towards `edge1 x edge2`), `samples` (default 1), `color`, `intensity` |
+//! | `sphere_light` | `center`, `radius`, `samples` (default 1), `color`, `intensity`           |
+//!
+//! A transform is any of `scale` (1 or 3 factors), `rotate` (axis and angle in
+//! degrees, 4 numbers) and `translate`, applied in this order, so a rotated
+//! `box` gives an oriented box. Transformed meshes are loaded once and shared
+//! between all their instances.
+//!
+//! Objects that take a transform can also move: `motion` lists offsets from
+//! their position as `time:x,y,z` keyframes separated by `;`, e.g.
+//! `motion=0:0,0,0;1:2,0,0`, and they are interpolated in between.
+//! With the shutter of the camera open over that time the object is blurred
+//! along its path.
+//!
+//! Any vector value can be animated by giving keyframes in the same form
+//! instead, e.g. `position=0:0,2,4;2:4,2,0` for a camera. They are evaluated
+//! once at the time of each frame, so unlike `motion` they do not blur.
+//! `interpolation` picks how to interpolate all keyframes of the directive:
+//! `linear` (the default), `smoothstep`, which eases in and out of every key,
+//! or `catmull_rom` for smooth curves through all keys.
+//! Frame `n` of an animation shows the scene at time `n / fps`, and the
+//! shutter times of the camera count from there.
+//!
+//! Objects that take a transform also take a `name`. A named object is not
+//! placed in the scene itself but can be combined by `csg`, which keeps the
+//! material of each child on its part of the surface, or filled by `volume`.
+//!
+//! Textures, materials and named objects have to be declared before whatever
+//! refers to them by name.
+//! A `mesh` without `material` uses the MTL libraries referenced by the OBJ file.
+//! The four `albedo` weights of a `phong` material scale its diffuse color,
+//! its specular highlight, its mirror reflection and its refraction. The
+//! refracted share `albedo[3]` is split between refraction and reflection
+//! according to the Fresnel equations.
+//! A `pbr` material reflects light off microfacets that get rougher with
+//! `roughness`, never more light than arrives. Metals tint the reflection
+//! with `base_color`, other materials reflect according to their
+//! `refractive_index` (default 1.5) and diffusely reflect the rest of the
+//! light in their `base_color`, or let the `transmission` share of it through.
+//! Lights do not fall off with distance, an unoccluded light of intensity 1
+//! lights a surface facing it with the surface's full diffuse color.
+//! Rays that miss everything see the `background`, a grey of 0.2 by default.
+//! The path integrator lights the scene with it, the Whitted integrator only
+//! if `samples` directions per diffuse hit are given to look for it in.
+//!
+//! `fog` fills the scene up to the view distance and `volume` fills a named
+//! object with a medium, rays pass the object's surface unchanged. Media dim
+//! the light passing them and scatter the light of the light sources in
+//! directions given by `g`: 0 evenly, positive values mostly onwards and
+//! negative ones back. Light sources and the background that are infinitely
+//! far away shine into the fog from outside of it.
+
+pub mod parser;
+
+use crate::backgrounds::background::Background;
+use crate::backgrounds::solid::SolidBackground;
+use crate::camera::Camera;
+use crate::lights::light::Light;
+use crate::media::medium::Medium;
+use crate::media::volume::Volume;
+use crate::objects::object::Object;
+use crate::raytracing::bvh::Bvh;
+use crate::raytracing::render::Integrator;
+use crate::raytracing::util::{BACKGROUND_COLOR, MAX_REFLECTION_DEPTH};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+pub use parser::SceneError;
+
+pub const DEFAULT_WIDTH: usize = 3840;
+pub const DEFAULT_HEIGHT: usize = 2160;
+pub const DEFAULT_SAMPLES: usize = 1;
+pub const DEFAULT_FPS: f32 = 24.0;
+pub const DEFAULT_MEDIUM_SAMPLES: usize = 4;
+
+pub struct Scene {
+    width: usize,
+    height: usize,
+    camera: Camera,
+    time: f32,
+    fps: f32,
+    samples: usize,
+    seed: u64,
+    integrator: Integrator,
+    max_depth: i32,
+    objects: Vec<Arc<dyn Object + Sync + Send>>,
+    bvh: Bvh,
+    lights: Vec<Arc<dyn Light + Sync + Send>>,
+    background: Arc<dyn Background + Sync + Send>,
+    background_samples: usize,
+    fog: Option<Medium>,
+    volumes: Vec<Volume>,
+    medium_samples: usize,
+}
+
+impl Scene {
+    pub fn new(
+        width: usize,
+        height: usize,
+        mut camera: Camera,
+        objects: Vec<Arc<dyn Object + Sync + Send>>,
+        lights: Vec<Arc<dyn Light + Sync + Send>>,
+    ) -> Self {
+        camera.set_aspect(width as f32 / height as f32);
+        Self {
+            width,
+            height,
+            camera,
+            time: 0.0,
+            fps: DEFAULT_FPS,
+            samples: DEFAULT_SAMPLES,
+            seed: 0,
+            integrator: Integrator::default(),
+            max_depth: MAX_REFLECTION_DEPTH,
+            bvh: Bvh::new(&objects),
+            objects,
+            lights,
+            background: Arc::new(SolidBackground::new(BACKGROUND_COLOR)),
+            background_samples: 0,
+            fog: None,
+            volumes: Vec::new(),
+            medium_samples: DEFAULT_MEDIUM_SAMPLES,
+        }
+    }
+
+    pub fn parse(source: &str) -> Result<Self, SceneError> {
+        Self::parse_at(source, 0.0)
+    }
+
+    /// Parses a scene with its keyframed values at `time`.
+    pub fn parse_at(source: &str, time: f32) -> Result<Self, SceneError> {
+        parser::parse(source, Path::new(""), time)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SceneError> {
+        Self::from_file_at(path, 0.0)
+    }
+
+    /// Loads a scene with its keyframed values at `time`.
+    pub fn from_file_at<P: AsRef<Path>>(path: P, time: f32) -> Result<Self, SceneError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(SceneError::Io)?;
+        parser::parse(
+            &source,
+            path.parent().unwrap_or_else(|| Path::new("")),
+            time,
+        )
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Time at which the keyframed values of the scene were evaluated.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Frames per second of an animation, frame `n` shows the scene at time
+    /// `n / fps`.
+    pub fn fps(&self) -> f32 {
+        self.fps
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn max_depth(&self) -> i32 {
+        self.max_depth
+    }
+
+    pub fn objects(&self) -> &Vec<Arc<dyn Object + Sync + Send>> {
+        &self.objects
+    }
+
+    pub fn bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    pub fn lights(&self) -> &Vec<Arc<dyn Light + Sync + Send>> {
+        &self.lights
+    }
+
+    pub fn background(&self) -> &Arc<dyn Background + Sync + Send> {
+        &self.background
+    }
+
+    /// Number of directions in which the Whitted integrator looks for the
+    /// background at every diffuse hit, to light the surface with it. 0
+    /// leaves the background unlit, the path integrator always lights with
+    /// it.
+    pub fn background_samples(&self) -> usize {
+        self.background_samples
+    }
+
+    /// Medium that fills all space up to the view distance.
+    pub fn fog(&self) -> Option<&Medium> {
+        self.fog.as_ref()
+    }
+
+    pub fn volumes(&self) -> &Vec<Volume> {
+        &self.volumes
+    }
+
+    /// Whether there is fog or any volume.
+    pub fn has_media(&self) -> bool {
+        self.fog.is_some() || !self.volumes.is_empty()
+    }
+
+    /// Number of points along every ray at which the Whitted integrator
+    /// gathers the light scattered by media.
+    pub fn medium_samples(&self) -> usize {
+        self.medium_samples
+    }
+
+    pub fn set_width(&mut self, width: usize) {
+        self.width = width;
+        self.camera
+            .set_aspect(self.width as f32 / self.height as f32);
+    }
+
+    pub fn set_height(&mut self, height: usize) {
+        self.height = height;
+        self.camera
+            .set_aspect(self.width as f32 / self.height as f32);
+    }
+
+    /// Replaces the camera, its aspect ratio is set to the one of the image.
+    pub fn set_camera(&mut self, mut camera: Camera) {
+        camera.set_aspect(self.width as f32 / self.height as f32);
+        self.camera = camera;
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps;
+    }
+
+    pub fn set_samples(&mut self, samples: usize) {
+        self.samples = samples;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: i32) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_background(&mut self, background: Arc<dyn Background + Sync + Send>) {
+        self.background = background;
+    }
+
+    pub fn set_background_samples(&mut self, background_samples: usize) {
+        self.background_samples = background_samples;
+    }
+
+    pub fn set_fog(&mut self, fog: Option<Medium>) {
+        self.fog = fog;
+    }
+
+    pub fn set_volumes(&mut self, volumes: Vec<Volume>) {
+        self.volumes = volumes;
+    }
+
+    pub fn set_medium_samples(&mut self, medium_samples: usize) {
+        self.medium_samples = medium_samples;
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_WIDTH,
+            DEFAULT_HEIGHT,
+            Camera::default(),
+            Vec::new(),
+            Vec::new(),
+        )
+    }
+}
@@ -0,0 +1,1720 @@
+use crate::animation::{Interpolation, Keyframes};
+use crate::backgrounds::background::Background;
+use crate::backgrounds::environment::EnvironmentMap;
+use crate::backgrounds::gradient::Gradient;
+use crate::backgrounds::sky::Sky;
+use crate::backgrounds::solid::SolidBackground;
+use crate::camera::Camera;
+use crate::lights::area::{RectLight, SphereLight};
+use crate::lights::directional::DirectionalLight;
+use crate::lights::light::Light;
+use crate::lights::point::PointLight;
+use crate::lights::spot::SpotLight;
+use crate::mat4::Mat4;
+use crate::material::{Material, Pbr};
+use crate::media::medium::Medium;
+use crate::media::volume::Volume;
+use crate::objects::cone::Cone;
+use crate::objects::csg::{Csg, CsgOp};
+use crate::objects::cuboid::Cuboid;
+use crate::objects::cylinder::Cylinder;
+use crate::objects::disk::Disk;
+use crate::objects::group::Group;
+use crate::objects::instance::Instance;
+use crate::objects::mesh::Mesh;
+use crate::objects::moving::Moving;
+use crate::objects::object::Object;
+use crate::objects::plane::Plane;
+use crate::objects::sphere::Sphere;
+use crate::objects::torus::Torus;
+use crate::objects::triangle::Triangle;
+use crate::raytracing::render::Integrator;
+use crate::scene::{
+    Scene, DEFAULT_FPS, DEFAULT_HEIGHT, DEFAULT_MEDIUM_SAMPLES, DEFAULT_SAMPLES, DEFAULT_WIDTH,
+};
+use crate::textures::checker::Checker;
+use crate::textures::image::ImageTexture;
+use crate::textures::noise::NoiseTexture;
+use crate::textures::solid::SolidColor;
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+use std::collections::HashMap;
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        column: usize,
+        key: Option<String>,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "cannot read scene: {}", err),
+            SceneError::Parse {
+                line,
+                column,
+                key: Some(key),
+                message,
+            } => write!(f, "{}:{}: `{}`: {}", line, column, key, message),
+            SceneError::Parse {
+                line,
+                column,
+                key: None,
+                message,
+            } => write!(f, "{}:{}: {}", line, column, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(err) => Some(err),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+struct Entry<'a> {
+    key: &'a str,
+    value: &'a str,
+    column: usize,
+    used: bool,
+}
+
+struct Directive<'a> {
+    kind: &'a str,
+    line: usize,
+    column: usize,
+    // Time at which keyframed values are evaluated.
+    time: f32,
+    entries: Vec<Entry<'a>>,
+}
+
+impl<'a> Directive<'a> {
+    fn tokenize(line: usize, text: &'a str, time: f32) -> Result<Option<Self>, SceneError> {
+        let text = match text.find('#') {
+            Some(comment) => &text[..comment],
+            None => text,
+        };
+
+        let mut tokens = text.split_whitespace().map(|token| {
+            let offset = token.as_ptr() as usize - text.as_ptr() as usize;
+            (text[..offset].chars().count() + 1, token)
+        });
+
+        let (column, kind) = match tokens.next() {
+            Some(token) => token,
+            None => return Ok(None),
+        };
+
+        let mut directive = Directive {
+            kind,
+            line,
+            column,
+            time,
+            entries: Vec::new(),
+        };
+
+        for (column, token) in tokens {
+            let (key, value) = match token.split_once('=') {
+                Some((key, value)) if !key.is_empty() && !value.is_empty() => (key, value),
+                _ => {
+                    return Err(directive.error(
+                        column,
+                        None,
+                        format!("expected `key=value`, got `{}`", token),
+                    ))
+                }
+            };
+            if directive.entries.iter().any(|e| e.key == key) {
+                return Err(directive.error(column, Some(key), "key is given twice".to_string()));
+            }
+            directive.entries.push(Entry {
+                key,
+                value,
+                column,
+                used: false,
+            });
+        }
+
+        Ok(Some(directive))
+    }
+
+    fn error(&self, column: usize, key: Option<&str>, message: String) -> SceneError {
+        SceneError::Parse {
+            line: self.line,
+            column,
+            key: key.map(str::to_string),
+            message,
+        }
+    }
+
+    fn take(&mut self, key: &str) -> Option<(&'a str, usize)> {
+        let entry = self.entries.iter_mut().find(|e| e.key == key)?;
+        entry.used = true;
+        Some((entry.value, entry.column))
+    }
+
+    fn required<T>(
+        &mut self,
+        key: &str,
+        get: fn(&mut Self, &str) -> Result<Option<T>, SceneError>,
+    ) -> Result<T, SceneError> {
+        get(self, key)?.ok_or_else(|| {
+            self.error(
+                self.column,
+                Some(key),
+                format!("missing required key for `{}`", self.kind),
+            )
+        })
+    }
+
+    fn floats<const N: usize>(&mut self, key: &str) -> Result<Option<[f32; N]>, SceneError> {
+        let (value, column) = match self.take(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let parts: Vec<&str> = value.split(',').collect();
+        let mut result = [0.0; N];
+        if parts.len() != N {
+            return Err(self.error(
+                column,
+                Some(key),
+                format!("expected {} comma separated numbers, got `{}`", N, value),
+            ));
+        }
+        for (slot, part) in result.iter_mut().zip(parts) {
+            *slot = match part.parse::<f32>() {
+                Ok(number) if number.is_finite() => number,
+                _ => {
+                    return Err(self.error(
+                        column,
+                        Some(key),
+                        format!("expected a number, got `{}`", part),
+                    ))
+                }
+            };
+        }
+
+        Ok(Some(result))
+    }
+
+    fn value<T: FromStr>(
+        &mut self,
+        key: &str,
+        expected: &str,
+        valid: impl Fn(&T) -> bool,
+    ) -> Result<Option<T>, SceneError> {
+        let (value, column) = match self.take(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        match value.parse::<T>() {
+            Ok(parsed) if valid(&parsed) => Ok(Some(parsed)),
+            _ => Err(self.error(
+                column,
+                Some(key),
+                format!("expected {}, got `{}`", expected, value),
+            )),
+        }
+    }
+
+    fn f32(&mut self, key: &str) -> Result<Option<f32>, SceneError> {
+        self.value(key, "a number", |x: &f32| x.is_finite())
+    }
+
+    fn positive_f32(&mut self, key: &str) -> Result<Option<f32>, SceneError> {
+        self.value(key, "a positive number", |x: &f32| {
+            x.is_finite() && *x > 0.0
+        })
+    }
+
+    fn angle(&mut self, key: &str) -> Result<Option<f32>, SceneError> {
+        Ok(self
+            .value(key, "an angle between 0 and 180 degrees", |x: &f32| {
+                *x > 0.0 && *x < 180.0
+            })?
+            .map(|angle| (angle / 180.0) * PI))
+    }
+
+    // The `color` and `intensity` keys shared by all lights.
+    fn light_color(&mut self) -> Result<(Vec3, f32), SceneError> {
+        let color = self.vec3("color")?.unwrap_or(Vec3::new(1.0, 1.0, 1.0));
+        let intensity = self
+            .value("intensity", "a non-negative number", |x: &f32| {
+                x.is_finite() && *x >= 0.0
+            })?
+            .unwrap_or(1.0);
+        Ok((color, intensity))
+    }
+
+    // The `g` key of media, 0 scatters light evenly in all directions.
+    fn phase_asymmetry(&mut self) -> Result<f32, SceneError> {
+        Ok(self
+            .value("g", "a number between -1 and 1", |x: &f32| {
+                *x > -1.0 && *x < 1.0
+            })?
+            .unwrap_or_default())
+    }
+
+    // One number for a uniform scale or three for each axis, none of them 0.
+    fn scale(&mut self, key: &str) -> Result<Option<Vec3>, SceneError> {
+        let (value, column) = match self.take(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let parts = value
+            .split(',')
+            .map(|part| {
+                part.parse::<f32>()
+                    .ok()
+                    .filter(|x| x.is_finite() && *x != 0.0)
+            })
+            .collect::<Option<Vec<f32>>>();
+        match parts.as_deref() {
+            Some(&[s]) => Ok(Some(Vec3::new(s, s, s))),
+            Some(&[x, y, z]) => Ok(Some(Vec3::new(x, y, z))),
+            _ => Err(self.error(
+                column,
+                Some(key),
+                format!(
+                    "expected 1 or 3 comma separated non-zero numbers, got `{}`",
+                    value
+                ),
+            )),
+        }
+    }
+
+    // The optional `translate`, `rotate` (axis and angle in degrees) and
+    // `scale` keys of an object, applied in the order scale, rotate, translate.
+    fn transform(&mut self) -> Result<Option<Mat4>, SceneError> {
+        let scale = self.scale("scale")?;
+        let rotate = match self.floats::<4>("rotate")? {
+            Some([x, y, z, angle]) => {
+                let axis = Vec3::new(x, y, z);
+                if axis.length() == 0.0 {
+                    return Err(self.error(
+                        self.column,
+                        Some("rotate"),
+                        "rotation axis must not be zero".to_string(),
+                    ));
+                }
+                Some(Mat4::rotation(axis, (angle / 180.0) * PI))
+            }
+            None => None,
+        };
+        let translate = self.vec3("translate")?;
+        if scale.is_none() && rotate.is_none() && translate.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(
+            Mat4::translation(translate.unwrap_or_default())
+                * rotate.unwrap_or_default()
+                * Mat4::scaling(scale.unwrap_or(Vec3::new(1.0, 1.0, 1.0))),
+        ))
+    }
+
+    // Rejects `value` when it equals `other`, e.g. the two ends of an axis.
+    fn distinct(
+        &self,
+        value: Vec3,
+        other: Vec3,
+        key: &str,
+        message: &str,
+    ) -> Result<(), SceneError> {
+        if value == other {
+            return Err(self.error(self.key_column(key), Some(key), message.to_string()));
+        }
+        Ok(())
+    }
+
+    // Vectors at increasing times, written as `time:x,y,z` and separated by
+    // `;`, interpolated as the `interpolation` key of the directive says.
+    fn keyframes(&mut self, key: &str) -> Result<Option<Keyframes<Vec3>>, SceneError> {
+        let (value, column) = match self.take(key) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let number = |s: &str| s.parse::<f32>().ok().filter(|x| x.is_finite());
+        let mut keys = Vec::new();
+        for keyframe in value.split(';') {
+            let parsed = keyframe.split_once(':').and_then(|(time, offset)| {
+                let parts: Option<Vec<f32>> = offset.split(',').map(number).collect();
+                match parts?[..] {
+                    [x, y, z] => Some((number(time)?, Vec3::new(x, y, z))),
+                    _ => None,
+                }
+            });
+            match parsed {
+                Some(keyframe) => keys.push(keyframe),
+                None => {
+                    return Err(self.error(
+                        column,
+                        Some(key),
+                        format!("expected `time:x,y,z` keyframes, got `{}`", keyframe),
+                    ))
+                }
+            }
+        }
+        if keys.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(self.error(
+                column,
+                Some(key),
+                "keyframe times must increase".to_string(),
+            ));
+        }
+        let mut keyframes = Keyframes::new(keys);
+        keyframes.set_interpolation(
+            self.value(
+                "interpolation",
+                "`linear`, `smoothstep` or `catmull_rom`",
+                |_: &Interpolation| true,
+            )?
+            .unwrap_or_default(),
+        );
+        Ok(Some(keyframes))
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.entries.iter().any(|e| e.key == key)
+    }
+
+    fn key_column(&self, key: &str) -> usize {
+        self.entries
+            .iter()
+            .find(|e| e.key == key)
+            .map_or(self.column, |e| e.column)
+    }
+
+    fn csg_op(&mut self, key: &str) -> Result<Option<CsgOp>, SceneError> {
+        self.value(
+            key,
+            "`union`, `intersection` or `difference`",
+            |_: &CsgOp| true,
+        )
+    }
+
+    fn usize(&mut self, key: &str) -> Result<Option<usize>, SceneError> {
+        self.value(key, "a positive integer", |n: &usize| *n > 0)
+    }
+
+    // A vector, or keyframes of it evaluated at the time of the scene.
+    fn vec3(&mut self, key: &str) -> Result<Option<Vec3>, SceneError> {
+        let animated = self
+            .entries
+            .iter()
+            .any(|e| e.key == key && e.value.contains(':'));
+        if animated {
+            let time = self.time;
+            return Ok(self.keyframes(key)?.map(|keyframes| keyframes.at(time)));
+        }
+        Ok(self.floats::<3>(key)?.map(|[x, y, z]| Vec3::new(x, y, z)))
+    }
+
+    fn str(&mut self, key: &str) -> Result<Option<(&'a str, usize)>, SceneError> {
+        Ok(self.take(key))
+    }
+
+    fn finish(self) -> Result<(), SceneError> {
+        match self.entries.iter().find(|e| !e.used) {
+            Some(entry) if entry.key == "interpolation" => Err(self.error(
+                entry.column,
+                Some(entry.key),
+                "no keyframes to interpolate".to_string(),
+            )),
+            Some(entry) => Err(self.error(
+                entry.column,
+                Some(entry.key),
+                format!("unknown key for `{}`", self.kind),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+struct Image {
+    width: usize,
+    height: usize,
+    fps: f32,
+    samples: usize,
+    medium_samples: usize,
+    seed: u64,
+    integrator: Integrator,
+}
+
+#[derive(Default)]
+struct Builder {
+    base: PathBuf,
+    time: f32,
+    image: Option<Image>,
+    camera: Option<Camera>,
+    textures: HashMap<String, Arc<dyn Texture + Sync + Send>>,
+    materials: HashMap<String, Material>,
+    // Meshes loaded for instancing, by path and material override.
+    meshes: HashMap<(PathBuf, Option<String>), Arc<Group>>,
+    // Named objects, which are only placed in the scene through `csg`.
+    shapes: HashMap<String, Arc<dyn Object + Sync + Send>>,
+    objects: Vec<Arc<dyn Object + Sync + Send>>,
+    lights: Vec<Arc<dyn Light + Sync + Send>>,
+    // The background and the number of samples to light the scene with it.
+    background: Option<(Arc<dyn Background + Sync + Send>, usize)>,
+    fog: Option<Medium>,
+    volumes: Vec<Volume>,
+}
+
+impl Builder {
+    fn directive(&mut self, mut d: Directive) -> Result<(), SceneError> {
+        match d.kind {
+            "image" => {
+                if self.image.is_some() {
+                    return Err(d.error(d.column, None, "`image` is given twice".to_string()));
+                }
+                self.image = Some(Image {
+                    width: d.usize("width")?.unwrap_or(DEFAULT_WIDTH),
+                    height: d.usize("height")?.unwrap_or(DEFAULT_HEIGHT),
+                    fps: d.positive_f32("fps")?.unwrap_or(DEFAULT_FPS),
+                    samples: d.usize("samples")?.unwrap_or(DEFAULT_SAMPLES),
+                    medium_samples: d.usize("medium_samples")?.unwrap_or(DEFAULT_MEDIUM_SAMPLES),
+                    seed: d
+                        .value("seed", "an unsigned integer", |_: &u64| true)?
+                        .unwrap_or_default(),
+                    integrator: d
+                        .value("integrator", "`whitted` or `path`", |_: &Integrator| true)?
+                        .unwrap_or_default(),
+                });
+            }
+            "camera" => {
+                if self.camera.is_some() {
+                    return Err(d.error(d.column, None, "`camera` is given twice".to_string()));
+                }
+                let default = Camera::default();
+                let position = d.vec3("position")?.unwrap_or(default.position());
+                let look_at = d.vec3("look_at")?.unwrap_or(default.look_at());
+                let up = d.vec3("up")?.unwrap_or(default.up());
+                let fov = d.angle("fov")?.unwrap_or(default.fov());
+                let aperture = d
+                    .value("aperture", "a number of at least 0", |x: &f32| {
+                        x.is_finite() && *x >= 0.0
+                    })?
+                    .unwrap_or(default.aperture());
+                let focus_distance = d.positive_f32("focus_distance")?;
+                let shutter_open = d.f32("shutter_open")?.unwrap_or(default.shutter_open());
+                let shutter_close = d.f32("shutter_close")?.unwrap_or(shutter_open);
+                if shutter_close < shutter_open {
+                    return Err(d.error(
+                        d.key_column("shutter_close"),
+                        Some("shutter_close"),
+                        "shutter closes before it opens".to_string(),
+                    ));
+                }
+
+                let forward = look_at - position;
+                if forward.length() == 0.0 {
+                    return Err(d.error(
+                        d.column,
+                        Some("look_at"),
+                        "camera looks at its own position".to_string(),
+                    ));
+                }
+                if forward.cross(up).length() <= f32::EPSILON * forward.length() * up.length() {
+                    return Err(d.error(
+                        d.column,
+                        Some("up"),
+                        "up vector is parallel to the viewing direction".to_string(),
+                    ));
+                }
+                let mut camera = Camera::new(position, look_at, up, fov, default.aspect());
+                camera.set_aperture(aperture);
+                // The shutter times count from the time of the frame.
+                camera.set_shutter(d.time + shutter_open, d.time + shutter_close);
+                if let Some(focus_distance) = focus_distance {
+                    camera.set_focus_distance(focus_distance);
+                }
+                self.camera = Some(camera);
+            }
+            "background" => {
+                if self.background.is_some() {
+                    return Err(d.error(d.column, None, "`background` is given twice".to_string()));
+                }
+                let non_negative = |d: &mut Directive, key: &str| {
+                    d.value(key, "a non-negative number", |x: &f32| {
+                        x.is_finite() && *x >= 0.0
+                    })
+                };
+                let (kind, column) = d.required("type", Directive::str)?;
+                let background: Arc<dyn Background + Sync + Send> = match kind {
+                    "solid" => {
+                        Arc::new(SolidBackground::new(d.required("color", Directive::vec3)?))
+                    }
+                    "gradient" => Arc::new(Gradient::new(
+                        d.required("bottom", Directive::vec3)?,
+                        d.required("top", Directive::vec3)?,
+                    )),
+                    "image" => {
+                        let (path, column) = d.required("path", Directive::str)?;
+                        let image = ImageTexture::load(self.base.join(path)).map_err(|err| {
+                            d.error(column, Some("path"), format!("{}: {}", path, err))
+                        })?;
+                        let mut map = EnvironmentMap::new(Arc::new(image));
+                        map.set_rotation(d.f32("rotate")?.unwrap_or_default() / 180.0 * PI);
+                        map.set_intensity(non_negative(&mut d, "intensity")?.unwrap_or(1.0));
+                        Arc::new(map)
+                    }
+                    "sky" => {
+                        let sun = d.required("sun", Directive::vec3)?;
+                        if sun.length() == 0.0 {
+                            return Err(d.error(
+                                d.key_column("sun"),
+                                Some("sun"),
+                                "direction must not be zero".to_string(),
+                            ));
+                        }
+                        let turbidity = d
+                            .value("turbidity", "a number between 1 and 10", |x: &f32| {
+                                (1.0..=10.0).contains(x)
+                            })?
+                            .unwrap_or(3.0);
+                        let intensity = non_negative(&mut d, "intensity")?.unwrap_or(1.0);
+                        let sky = Sky::new(sun, turbidity, intensity);
+                        let sun_intensity = non_negative(&mut d, "sun_intensity")?.unwrap_or(1.0);
+                        if sun_intensity > 0.0 {
+                            self.lights.push(Arc::new(DirectionalLight::new(
+                                -sky.sun(),
+                                sky.sun_color(),
+                                sun_intensity,
+                            )));
+                        }
+                        Arc::new(sky)
+                    }
+                    kind => {
+                        return Err(d.error(
+                            column,
+                            Some("type"),
+                            format!(
+                                "expected `solid`, `gradient`, `image` or `sky`, got `{}`",
+                                kind
+                            ),
+                        ))
+                    }
+                };
+                let samples = d.usize("samples")?.unwrap_or_default();
+                self.background = Some((background, samples));
+            }
+            "texture" => {
+                let (name, column) = d.required("name", Directive::str)?;
+                if self.textures.contains_key(name) {
+                    return Err(d.error(
+                        column,
+                        Some("name"),
+                        format!("texture `{}` is already defined", name),
+                    ));
+                }
+                let (kind, column) = d.required("type", Directive::str)?;
+                let texture: Arc<dyn Texture + Sync + Send> = match kind {
+                    "solid" => Arc::new(SolidColor::new(d.required("color", Directive::vec3)?)),
+                    "checker" => Arc::new(Checker::new(
+                        Arc::new(SolidColor::new(d.required("even", Directive::vec3)?)),
+                        Arc::new(SolidColor::new(d.required("odd", Directive::vec3)?)),
+                        d.positive_f32("scale")?.unwrap_or(8.0),
+                    )),
+                    "noise" => Arc::new(NoiseTexture::new(
+                        d.vec3("color")?.unwrap_or(Vec3::new(1.0, 1.0, 1.0)),
+                        d.positive_f32("scale")?.unwrap_or(1.0),
+                        d.value("seed", "an unsigned integer", |_: &u64| true)?
+                            .unwrap_or_default(),
+                    )),
+                    "image" => {
+                        let (path, column) = d.required("path", Directive::str)?;
+                        let image = ImageTexture::load(self.base.join(path)).map_err(|err| {
+                            d.error(column, Some("path"), format!("{}: {}", path, err))
+                        })?;
+                        Arc::new(image)
+                    }
+                    kind => {
+                        return Err(d.error(
+                            column,
+                            Some("type"),
+                            format!(
+                                "expected `solid`, `checker`, `noise` or `image`, got `{}`",
+                                kind
+                            ),
+                        ))
+                    }
+                };
+                self.textures.insert(name.to_string(), texture);
+            }
+            "material" => {
+                let (name, column) = d.required("name", Directive::str)?;
+                if self.materials.contains_key(name) {
+                    return Err(d.error(
+                        column,
+                        Some("name"),
+                        format!("material `{}` is already defined", name),
+                    ));
+                }
+                let default = Material::default();
+                let (kind, column) = d.str("type")?.unwrap_or(("phong", d.column));
+                let mut material = match kind {
+                    "phong" => Material::new(
+                        d.positive_f32("refractive_index")?
+                            .unwrap_or(default.refractive_index()),
+                        d.floats::<4>("albedo")?.unwrap_or(*default.albedo()),
+                        d.vec3("diffuse")?.unwrap_or(default.diffuse_color()),
+                        d.f32("specular_exponent")?
+                            .unwrap_or(default.specular_exponent()),
+                    ),
+                    "pbr" => {
+                        let fraction = |d: &mut Directive, key: &str| {
+                            d.value(key, "a number between 0 and 1", |x: &f32| {
+                                (0.0..=1.0).contains(x)
+                            })
+                        };
+                        let default = Pbr::default();
+                        let pbr = Pbr {
+                            metallic: fraction(&mut d, "metallic")?.unwrap_or(default.metallic),
+                            roughness: fraction(&mut d, "roughness")?.unwrap_or(default.roughness),
+                            transmission: fraction(&mut d, "transmission")?
+                                .unwrap_or(default.transmission),
+                        };
+                        Material::pbr(
+                            d.vec3("base_color")?.unwrap_or(Vec3::new(0.8, 0.8, 0.8)),
+                            pbr,
+                            d.positive_f32("refractive_index")?.unwrap_or(1.5),
+                        )
+                    }
+                    kind => {
+                        return Err(d.error(
+                            column,
+                            Some("type"),
+                            format!("expected `phong` or `pbr`, got `{}`", kind),
+                        ))
+                    }
+                };
+                if let Some(emission) = d.vec3("emission")? {
+                    material.set_emission(emission);
+                }
+                if let Some(absorption) = d.vec3("absorption")? {
+                    material.set_absorption(absorption);
+                }
+                if let Some((name, column)) = d.str("texture")? {
+                    let texture = self.textures.get(name).cloned().ok_or_else(|| {
+                        d.error(
+                            column,
+                            Some("texture"),
+                            format!("unknown texture `{}`", name),
+                        )
+                    })?;
+                    material.set_texture(Some(texture));
+                }
+                // IDs count the named materials from 1 in order of declaration.
+                material.set_id(self.materials.len() + 1);
+                self.materials.insert(name.to_string(), material);
+            }
+            "sphere" => {
+                let center = d.required("center", Directive::vec3)?;
+                let radius = d.required("radius", Directive::positive_f32)?;
+                let material = self.material(&mut d)?;
+                self.place(&mut d, Arc::new(Sphere::new(center, radius, material)))?;
+            }
+            "box" => {
+                let min = d.required("min", Directive::vec3)?;
+                let max = d.required("max", Directive::vec3)?;
+                if (0..3).any(|axis| min[axis] == max[axis]) {
+                    return Err(d.error(
+                        d.key_column("max"),
+                        Some("max"),
+                        "box must have a non-zero size along every axis".to_string(),
+                    ));
+                }
+                let material = self.material(&mut d)?;
+                self.place(&mut d, Arc::new(Cuboid::new(min, max, material)))?;
+            }
+            "cylinder" => {
+                let base = d.required("base", Directive::vec3)?;
+                let top = d.required("top", Directive::vec3)?;
+                d.distinct(top, base, "top", "cylinder top must differ from its base")?;
+                let radius = d.required("radius", Directive::positive_f32)?;
+                let material = self.material(&mut d)?;
+                self.place(&mut d, Arc::new(Cylinder::new(base, top, radius, material)))?;
+            }
+            "cone" => {
+                let base = d.required("base", Directive::vec3)?;
+                let apex = d.required("apex", Directive::vec3)?;
+                d.distinct(apex, base, "apex", "cone apex must differ from its base")?;
+                let radius = d.required("radius", Directive::positive_f32)?;
+                let material = self.material(&mut d)?;
+                self.place(&mut d, Arc::new(Cone::new(base, apex, radius, material)))?;
+            }
+            "disk" => {
+                let center = d.required("center", Directive::vec3)?;
+                let normal = d.required("normal", Directive::vec3)?;
+                d.distinct(
+                    normal,
+                    Vec3::default(),
+                    "normal",
+                    "disk normal must not be zero",
+                )?;
+                let radius = d.required("radius", Directive::positive_f32)?;
+                let material = self.material(&mut d)?;
+                self.place(
+                    &mut d,
+                    Arc::new(Disk::new(center, normal, radius, material)),
+                )?;
+            }
+            "torus" => {
+                let center = d.required("center", Directive::vec3)?;
+                let axis = d.vec3("axis")?.unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                d.distinct(axis, Vec3::default(), "axis", "torus axis must not be zero")?;
+                let major_radius = d.required("major_radius", Directive::positive_f32)?;
+                let minor_radius = d.required("minor_radius", Directive::positive_f32)?;
+                let material = self.material(&mut d)?;
+                self.place(
+                    &mut d,
+                    Arc::new(Torus::new(
+                        center,
+                        axis,
+                        major_radius,
+                        minor_radius,
+                        material,
+                    )),
+                )?;
+            }
+            "plane" => {
+                let center = d.required("center", Directive::vec3)?;
+                let normal = d.required("normal", Directive::vec3)?;
+                d.distinct(
+                    normal,
+                    Vec3::default(),
+                    "normal",
+                    "plane normal must not be zero",
+                )?;
+                let size = d.positive_f32("size")?;
+                let width = d.positive_f32("width")?;
+                let height = d.positive_f32("height")?;
+                let material = match d.str("material")? {
+                    Some((name, column)) => Some(self.lookup(&d, name, column)?),
+                    None => None,
+                };
+                // Squares and infinite planes get unit checkerboard squares.
+                let mut plane = match (size, width, height) {
+                    (None, None, None) => Plane::infinite(
+                        center,
+                        normal,
+                        material.unwrap_or_else(|| Plane::checkerboard(1.0)),
+                    ),
+                    (Some(size), None, None) => Plane::new(
+                        center,
+                        normal,
+                        size,
+                        material.unwrap_or_else(|| Plane::checkerboard(size)),
+                    ),
+                    (None, Some(width), Some(height)) => Plane::rectangle(
+                        center,
+                        normal,
+                        Vec3::new(1.0, 0.0, 0.0),
+                        width,
+                        height,
+                        material.unwrap_or_else(|| Plane::checkerboard(width.max(height))),
+                    ),
+                    (Some(_), _, _) => {
+                        return Err(d.error(
+                            d.key_column("size"),
+                            Some("size"),
+                            "`size` cannot be combined with `width` and `height`".to_string(),
+                        ))
+                    }
+                    (None, Some(_), None) => {
+                        return Err(d.error(
+                            d.column,
+                            Some("height"),
+                            "`width` requires `height`".to_string(),
+                        ))
+                    }
+                    (None, None, Some(_)) => {
+                        return Err(d.error(
+                            d.column,
+                            Some("width"),
+                            "`height` requires `width`".to_string(),
+                        ))
+                    }
+                };
+                if let Some(tangent) = d.vec3("tangent")? {
+                    plane.set_tangent(tangent);
+                }
+                self.place(&mut d, Arc::new(plane))?;
+            }
+            "csg" => {
+                let op = d.required("op", Directive::csg_op)?;
+                let left = self.shape(&mut d, "left")?;
+                let right = self.shape(&mut d, "right")?;
+                self.place(&mut d, Arc::new(Csg::new(op, left, right)))?;
+            }
+            "triangle" => {
+                let a = d.required("a", Directive::vec3)?;
+                let b = d.required("b", Directive::vec3)?;
+                let c = d.required("c", Directive::vec3)?;
+                let material = self.material(&mut d)?;
+                self.objects
+                    .push(Arc::new(Triangle::new([a, b, c], material)));
+            }
+            "mesh" => {
+                let (path, column) = d.required("path", Directive::str)?;
+                let (material_name, material) = match d.str("material")? {
+                    Some((name, column)) => (Some(name), Some(self.lookup(&d, name, column)?)),
+                    None => (None, None),
+                };
+                let path = self.base.join(path);
+                let key = (path.clone(), material_name.map(str::to_string));
+                let load = |d: &Directive| {
+                    Mesh::load_obj(&path, material)
+                        .map_err(|err| d.error(column, Some("path"), err.to_string()))
+                };
+
+                // A transformed or named mesh becomes a group that is shared
+                // by all uses of the same file, a plain one adds its triangles
+                // to the scene directly.
+                let shared = ["name", "scale", "rotate", "translate", "motion"]
+                    .iter()
+                    .any(|key| d.has(key));
+                if shared {
+                    let group = match self.meshes.get(&key) {
+                        Some(group) => group.clone(),
+                        None => {
+                            let group = Arc::new(Group::new(load(&d)?.into_objects()));
+                            self.meshes.insert(key, group.clone());
+                            group
+                        }
+                    };
+                    self.place(&mut d, group)?;
+                } else {
+                    self.objects.extend(load(&d)?.into_objects());
+                }
+            }
+            "fog" => {
+                if self.fog.is_some() {
+                    return Err(d.error(d.column, None, "`fog` is given twice".to_string()));
+                }
+                let density = d.required("density", Directive::positive_f32)?;
+                let color = d.vec3("color")?.unwrap_or(Vec3::new(1.0, 1.0, 1.0));
+                let mut fog = Medium::with_density(density, color);
+                fog.set_g(d.phase_asymmetry()?);
+                self.fog = Some(fog);
+            }
+            "volume" => {
+                let boundary = self.shape(&mut d, "shape")?;
+                let absorption = d.vec3("absorption")?.unwrap_or_default();
+                let scattering = d.vec3("scattering")?.unwrap_or_default();
+                let g = d.phase_asymmetry()?;
+                self.volumes.push(Volume::new(
+                    boundary,
+                    Medium::new(absorption, scattering, g),
+                ));
+            }
+            "light" => {
+                let position = d.required("position", Directive::vec3)?;
+                let (color, intensity) = d.light_color()?;
+                self.lights
+                    .push(Arc::new(PointLight::new(position, color, intensity)));
+            }
+            "directional_light" => {
+                let direction = d.required("direction", Directive::vec3)?;
+                if direction.length() == 0.0 {
+                    return Err(d.error(
+                        d.column,
+                        Some("direction"),
+                        "direction must not be zero".to_string(),
+                    ));
+                }
+                let (color, intensity) = d.light_color()?;
+                self.lights
+                    .push(Arc::new(DirectionalLight::new(direction, color, intensity)));
+            }
+            "spot_light" => {
+                let position = d.required("position", Directive::vec3)?;
+                let direction = d.required("direction", Directive::vec3)?;
+                if direction.length() == 0.0 {
+                    return Err(d.error(
+                        d.column,
+                        Some("direction"),
+                        "direction must not be zero".to_string(),
+                    ));
+                }
+                let angle = d.required("angle", Directive::angle)?;
+                let inner_angle = d.angle("inner_angle")?.unwrap_or(angle);
+                if inner_angle > angle {
+                    return Err(d.error(
+                        d.key_column("inner_angle"),
+                        Some("inner_angle"),
+                        "inner angle must not exceed `angle`".to_string(),
+                    ));
+                }
+                let (color, intensity) = d.light_color()?;
+                self.lights.push(Arc::new(SpotLight::new(
+                    position,
+                    direction,
+                    inner_angle,
+                    angle,
+                    color,
+                    intensity,
+                )));
+            }
+            "rect_light" => {
+                let corner = d.required("corner", Directive::vec3)?;
+                let edge1 = d.required("edge1", Directive::vec3)?;
+                let edge2 = d.required("edge2", Directive::vec3)?;
+                if edge1.cross(edge2).length() == 0.0 {
+                    return Err(d.error(
+                        d.column,
+                        Some("edge2"),
+                        "edges do not span a rectangle".to_string(),
+                    ));
+                }
+                let samples = d.usize("samples")?.unwrap_or(1);
+                let (color, intensity) = d.light_color()?;
+                self.lights.push(Arc::new(RectLight::new(
+                    corner, edge1, edge2, color, intensity, samples,
+                )));
+            }
+            "sphere_light" => {
+                let center = d.required("center", Directive::vec3)?;
+                let radius = d.required("radius", Directive::positive_f32)?;
+                let samples = d.usize("samples")?.unwrap_or(1);
+                let (color, intensity) = d.light_color()?;
+                self.lights.push(Arc::new(SphereLight::new(
+                    center, radius, color, intensity, samples,
+                )));
+            }
+            kind => {
+                return Err(d.error(d.column, None, format!("unknown directive `{}`", kind)));
+            }
+        }
+
+        d.finish()
+    }
+
+    // Adds an object with the optional transform and motion of the directive
+    // to the scene, or keeps it for later reference if the directive names it.
+    fn place(
+        &mut self,
+        d: &mut Directive,
+        object: Arc<dyn Object + Sync + Send>,
+    ) -> Result<(), SceneError> {
+        let object = match d.transform()? {
+            Some(transform) => match Instance::new(object, transform) {
+                Some(instance) => Arc::new(instance),
+                None => {
+                    // Only a tiny scale can make the transform singular.
+                    return Err(d.error(
+                        d.key_column("scale"),
+                        Some("scale"),
+                        "transform is not invertible".to_string(),
+                    ));
+                }
+            },
+            None => object,
+        };
+        let object = match d.keyframes("motion")? {
+            Some(path) => Arc::new(Moving::new(object, path)),
+            None => object,
+        };
+        match d.str("name")? {
+            Some((name, _)) => {
+                self.shapes.insert(name.to_string(), object);
+            }
+            None => self.objects.push(object),
+        }
+        Ok(())
+    }
+
+    fn shape(
+        &self,
+        d: &mut Directive,
+        key: &str,
+    ) -> Result<Arc<dyn Object + Sync + Send>, SceneError> {
+        let (name, column) = d.required(key, Directive::str)?;
+        self.shapes
+            .get(name)
+            .cloned()
+            .ok_or_else(|| d.error(column, Some(key), format!("unknown object `{}`", name)))
+    }
+
+    fn material(&self, d: &mut Directive) -> Result<Material, SceneError> {
+        let (name, column) = d.required("material", Directive::str)?;
+        self.lookup(d, name, column)
+    }
+
+    fn lookup(&self, d: &Directive, name: &str, column: usize) -> Result<Material, SceneError> {
+        self.materials.get(name).cloned().ok_or_else(|| {
+            d.error(
+                column,
+                Some("material"),
+                format!("unknown material `{}`", name),
+            )
+        })
+    }
+
+    fn build(self) -> Scene {
+        let image = self.image.unwrap_or(Image {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            fps: DEFAULT_FPS,
+            samples: DEFAULT_SAMPLES,
+            medium_samples: DEFAULT_MEDIUM_SAMPLES,
+            seed: 0,
+            integrator: Integrator::default(),
+        });
+        let mut scene = Scene::new(
+            image.width,
+            image.height,
+            self.camera.unwrap_or_default(),
+            self.objects,
+            self.lights,
+        );
+        scene.set_time(self.time);
+        scene.set_fps(image.fps);
+        scene.set_samples(image.samples);
+        scene.set_medium_samples(image.medium_samples);
+        scene.set_seed(image.seed);
+        scene.set_integrator(image.integrator);
+        scene.set_fog(self.fog);
+        scene.set_volumes(self.volumes);
+        if let Some((background, samples)) = self.background {
+            scene.set_background(background);
+            scene.set_background_samples(samples);
+        }
+        scene
+    }
+}
+
+/// Parses a scene with its keyframed values at `time`, relative mesh paths
+/// are resolved against `base`.
+pub fn parse(source: &str, base: &Path, time: f32) -> Result<Scene, SceneError> {
+    let mut builder = Builder {
+        base: base.to_path_buf(),
+        time,
+        ..Builder::default()
+    };
+
+    for (index, text) in source.lines().enumerate() {
+        if let Some(directive) = Directive::tokenize(index + 1, text, time)? {
+            builder.directive(directive)?;
+        }
+    }
+
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::material::{Model, Pbr};
+    use crate::objects::object::Object;
+    use crate::ray::Ray;
+    use crate::raytracing::render::Integrator;
+    use crate::raytracing::sampling::pixel_rng;
+    use crate::raytracing::util::EPS;
+    use crate::scene::{Scene, SceneError};
+    use crate::vec3::Vec3;
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    fn distance(object: &Arc<dyn Object + Sync + Send>, orig: Vec3, dir: Vec3) -> Option<f32> {
+        object
+            .intersect(&Ray::new(orig, dir), EPS, f32::INFINITY)
+            .map(|hit| hit.t)
+    }
+
+    fn parse_error(source: &str) -> (usize, usize, Option<String>) {
+        match Scene::parse(source) {
+            Err(SceneError::Parse {
+                line, column, key, ..
+            }) => (line, column, key),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("scene parsed successfully"),
+        }
+    }
+
+    #[test]
+    fn test_parse_scene() {
+        let scene = Scene::parse(
+            "# comment\n\
+             image width=640 height=480 samples=4 seed=12 integrator=path\n\
+             camera fov=90\n\
+             material name=red albedo=1,0,0,0 diffuse=1,0,0 # trailing comment\n\
+             sphere center=0,0,-5 radius=1 material=red\n\
+             plane center=0,-1,0 normal=0,1,0 size=10\n\
+             light position=10,10,10\n",
+        )
+        .unwrap();
+
+        assert_eq!(640, scene.width());
+        assert_eq!(480, scene.height());
+        assert_eq!(4, scene.samples());
+        assert_eq!(12, scene.seed());
+        assert_eq!(Integrator::Path, scene.integrator());
+        assert_eq!(PI / 2.0, scene.camera().fov());
+        assert_eq!(640.0 / 480.0, scene.camera().aspect());
+        assert_eq!(2, scene.objects().len());
+        assert_eq!(1, scene.lights().len());
+    }
+
+    #[test]
+    fn test_parse_absorption() {
+        let scene = Scene::parse(
+            "material name=clear albedo=0,0.5,0,0.9\n\
+             material name=tinted albedo=0,0.5,0,0.9 absorption=0.1,0.2,0.3\n\
+             sphere center=0,0,-5 radius=1 material=tinted\n",
+        )
+        .unwrap();
+        let hit = scene.objects()[0]
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        let material = hit.material;
+        assert_eq!(Vec3::new(0.1, 0.2, 0.3), material.absorption());
+        assert_eq!(2, material.id());
+        let t = material.transmittance(10.0);
+        assert!((t.x() - (-1.0f32).exp()).abs() < 1e-6 && t.z() < t.y());
+    }
+
+    #[test]
+    fn test_parse_pbr_material() {
+        let scene = Scene::parse(
+            "material name=phong albedo=0.6,0.3,0.1,0\n\
+             material name=gold type=pbr base_color=1,0.8,0.3 metallic=1 roughness=0.2\n\
+             sphere center=0,0,-5 radius=1 material=gold\n\
+             sphere center=0,0,5 radius=1 material=phong\n",
+        )
+        .unwrap();
+        let material = |object: usize, dir: Vec3| {
+            scene.objects()[object]
+                .intersect(&Ray::new(Vec3::default(), dir), EPS, f32::INFINITY)
+                .unwrap()
+                .material
+                .clone()
+        };
+        let gold = material(0, Vec3::new(0.0, 0.0, -1.0));
+        let pbr = Pbr {
+            metallic: 1.0,
+            roughness: 0.2,
+            transmission: 0.0,
+        };
+        assert_eq!(Model::Pbr(pbr), gold.model());
+        assert_eq!(Vec3::new(1.0, 0.8, 0.3), gold.diffuse_color());
+        assert_eq!(1.5, gold.refractive_index());
+        assert_eq!(Model::Phong, material(1, Vec3::new(0.0, 0.0, 1.0)).model());
+
+        assert_eq!(
+            (1, 17, Some("type".to_string())),
+            parse_error("material name=m type=disney")
+        );
+        assert_eq!(
+            (1, 26, Some("roughness".to_string())),
+            parse_error("material name=m type=pbr roughness=2")
+        );
+        // The models do not share their color and weight keys.
+        assert_eq!(
+            (1, 17, Some("albedo".to_string())),
+            parse_error("material name=m albedo=1,0,0,0 type=pbr")
+        );
+        assert_eq!(
+            (1, 17, Some("metallic".to_string())),
+            parse_error("material name=m metallic=1")
+        );
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let scene = Scene::parse("light position=0,1,0").unwrap();
+        assert_eq!(3840, scene.width());
+        assert_eq!(2160, scene.height());
+        assert_eq!(PI / 3.0, scene.camera().fov());
+        assert_eq!(Vec3::default(), scene.camera().position());
+    }
+
+    #[test]
+    fn test_parse_bad_value() {
+        assert_eq!(
+            (2, 9, Some("radius".to_string())),
+            parse_error("material name=m\nsphere  radius=abc center=0,0,0 material=m")
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_key() {
+        assert_eq!(
+            (1, 27, Some("radius".to_string())),
+            parse_error("light position=1,2,3      radius=1")
+        );
+    }
+
+    #[test]
+    fn test_parse_lights() {
+        let scene = Scene::parse(
+            "light position=0,5,0 color=1,0.5,0 intensity=2\n\
+             directional_light direction=0,-1,0\n\
+             spot_light position=0,5,0 direction=0,-1,0 angle=30 inner_angle=20\n\
+             rect_light corner=-1,5,-1 edge1=2,0,0 edge2=0,0,2 samples=4\n\
+             sphere_light center=0,5,0 radius=0.5 samples=8 intensity=0.5\n",
+        )
+        .unwrap();
+        assert_eq!(5, scene.lights().len());
+
+        let mut rng = pixel_rng(0, 0);
+        let samples = scene.lights()[0].samples(Vec3::default(), &mut rng);
+        assert_eq!(Vec3::new(2.0, 1.0, 0.0), samples[0].intensity);
+        assert_eq!(
+            4,
+            scene.lights()[3].samples(Vec3::default(), &mut rng).len()
+        );
+
+        assert_eq!(
+            (1, 1, Some("direction".to_string())),
+            parse_error("directional_light direction=0,0,0")
+        );
+        assert_eq!(
+            (1, 43, Some("angle".to_string())),
+            parse_error("spot_light position=0,0,0 direction=0,0,1 angle=180")
+        );
+        assert_eq!(
+            (1, 1, Some("direction".to_string())),
+            parse_error("spot_light position=0,0,0 direction=0,0,0 angle=30")
+        );
+        assert_eq!(
+            (1, 52, Some("inner_angle".to_string())),
+            parse_error("spot_light position=0,0,0 direction=0,0,1 angle=30 inner_angle=60")
+        );
+        assert_eq!(
+            (1, 1, Some("edge2".to_string())),
+            parse_error("rect_light corner=0,0,0 edge1=1,0,0 edge2=2,0,0")
+        );
+    }
+
+    #[test]
+    fn test_parse_textures() {
+        let scene = Scene::parse(
+            "texture name=board type=checker even=1,1,1 odd=0,0,0 scale=2\n\
+             texture name=marble type=noise scale=4 seed=3\n\
+             material name=tiles texture=board\n\
+             plane center=0,0,0 normal=0,1,0 size=2 material=tiles\n",
+        )
+        .unwrap();
+        let hit = scene.objects()[0]
+            .intersect(
+                &Ray::new(Vec3::new(-0.9, 1.0, -0.9), Vec3::new(0.0, -1.0, 0.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        let (u, v) = hit.uv;
+        assert_eq!(
+            Vec3::new(1.0, 1.0, 1.0),
+            hit.material.diffuse_color_at(u, v, hit.point)
+        );
+
+        assert_eq!(
+            (1, 16, Some("type".to_string())),
+            parse_error("texture name=t type=wood")
+        );
+        assert_eq!(
+            (1, 17, Some("texture".to_string())),
+            parse_error("material name=m texture=missing")
+        );
+        assert_eq!(
+            (1, 27, Some("path".to_string())),
+            parse_error("texture name=t type=image path=missing.png")
+        );
+    }
+
+    #[test]
+    fn test_parse_backgrounds() {
+        let up = Vec3::new(0.0, 1.0, 0.0);
+        let scene = Scene::parse("").unwrap();
+        assert_eq!(Vec3::new(0.2, 0.2, 0.2), scene.background().radiance(up));
+        assert_eq!(0, scene.background_samples());
+
+        let scene =
+            Scene::parse("background type=gradient bottom=0,0,0 top=0,0,1 samples=8").unwrap();
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), scene.background().radiance(up));
+        assert_eq!(8, scene.background_samples());
+
+        // The sky brings a sun unless it is turned off.
+        let scene = Scene::parse("background type=sky sun=0,1,-1 turbidity=4").unwrap();
+        assert_eq!(1, scene.lights().len());
+        let sky = scene.background().radiance(up);
+        assert!(sky.z() > sky.x());
+        let scene = Scene::parse("background type=sky sun=0,1,-1 sun_intensity=0").unwrap();
+        assert!(scene.lights().is_empty());
+
+        assert_eq!(
+            (1, 12, Some("type".to_string())),
+            parse_error("background type=stars")
+        );
+        assert_eq!(
+            (1, 21, Some("sun".to_string())),
+            parse_error("background type=sky sun=0,0,0")
+        );
+        assert_eq!(
+            (1, 23, Some("path".to_string())),
+            parse_error("background type=image path=missing.hdr")
+        );
+        assert_eq!(
+            (2, 1, None),
+            parse_error("background type=solid color=1,1,1\nbackground type=solid color=0,0,0")
+        );
+    }
+
+    #[test]
+    fn test_parse_media() {
+        let scene = Scene::parse(
+            "image medium_samples=2\n\
+             fog density=0.05 color=0.8,0.8,0.9 g=0.3\n\
+             material name=m\n\
+             sphere name=smoke center=0,0,-5 radius=1 material=m\n\
+             volume shape=smoke absorption=0.1,0.1,0.1 scattering=2,2,2 g=-0.2",
+        )
+        .unwrap();
+        assert_eq!(2, scene.medium_samples());
+        let fog = scene.fog().unwrap();
+        assert!((fog.scattering() - Vec3::new(0.04, 0.04, 0.045)).length() < 1e-6);
+        assert_eq!(0.3, fog.g());
+        assert_eq!(1, scene.volumes().len());
+        assert_eq!(
+            Vec3::new(2.0, 2.0, 2.0),
+            scene.volumes()[0].medium().scattering()
+        );
+        assert_eq!(-0.2, scene.volumes()[0].medium().g());
+        assert!(scene.objects().is_empty());
+        assert!(Scene::parse("").unwrap().fog().is_none());
+
+        assert_eq!(
+            (1, 5, Some("density".to_string())),
+            parse_error("fog density=0")
+        );
+        assert_eq!(
+            (1, 15, Some("g".to_string())),
+            parse_error("fog density=1 g=1")
+        );
+        assert_eq!(
+            (1, 8, Some("shape".to_string())),
+            parse_error("volume shape=missing")
+        );
+        assert_eq!((2, 1, None), parse_error("fog density=1\nfog density=2"));
+    }
+
+    #[test]
+    fn test_parse_unknown_material() {
+        assert_eq!(
+            (1, 33, Some("material".to_string())),
+            parse_error("sphere center=0,0,0 radius=1    material=glass")
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_key() {
+        assert_eq!(
+            (3, 3, Some("position".to_string())),
+            parse_error("\n\n  light")
+        );
+    }
+
+    #[test]
+    fn test_parse_mesh() {
+        let dir = std::env::temp_dir().join("raytracer_test_parse_mesh");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tri.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        std::fs::write(dir.join("test.scene"), "mesh path=tri.obj\n").unwrap();
+
+        let scene = Scene::from_file(dir.join("test.scene")).unwrap();
+        assert_eq!(1, scene.objects().len());
+
+        assert_eq!(
+            (1, 6, Some("path".to_string())),
+            parse_error("mesh path=missing.obj")
+        );
+    }
+
+    #[test]
+    fn test_parse_transforms() {
+        let dir = std::env::temp_dir().join("raytracer_test_parse_transforms");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tri.obj"), "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        std::fs::write(
+            dir.join("test.scene"),
+            "material name=m\n\
+             sphere center=0,0,0 radius=1 material=m scale=2,1,1 translate=0,0,-10\n\
+             mesh path=tri.obj translate=0,0,-5\n\
+             mesh path=tri.obj rotate=0,0,1,90 scale=3 translate=10,0,0\n",
+        )
+        .unwrap();
+
+        let scene = Scene::from_file(dir.join("test.scene")).unwrap();
+        assert_eq!(3, scene.objects().len());
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+
+        let d = distance(&scene.objects()[0], Vec3::new(1.5, 0.0, 0.0), forward).unwrap();
+        assert!((d - 10.0 + (1.0f32 - 0.75 * 0.75).sqrt()).abs() < 1e-4);
+
+        let d = distance(&scene.objects()[1], Vec3::new(0.2, 0.2, 0.0), forward).unwrap();
+        assert!((d - 5.0).abs() < 1e-5);
+
+        // Rotated by 90 degrees the triangle lies left of x = 10.
+        let mesh = &scene.objects()[2];
+        assert!(distance(mesh, Vec3::new(9.0, 1.0, 1.0), forward).is_some());
+        assert!(distance(mesh, Vec3::new(11.0, 1.0, 1.0), forward).is_none());
+
+        assert_eq!(
+            (2, 41, Some("scale".to_string())),
+            parse_error("material name=m\nsphere center=0,0,0 radius=1 material=m scale=0")
+        );
+        assert_eq!(
+            (2, 41, Some("scale".to_string())),
+            parse_error("material name=m\nsphere center=0,0,0 radius=1 material=m scale=1e-13")
+        );
+        assert_eq!(
+            (2, 1, Some("rotate".to_string())),
+            parse_error("material name=m\nsphere center=0,0,0 radius=1 material=m rotate=0,0,0,10")
+        );
+    }
+
+    #[test]
+    fn test_parse_primitives() {
+        let scene = Scene::parse(
+            "material name=m\n\
+             box min=-1,-1,-6 max=1,1,-4 material=m\n\
+             box min=-1,-1,-1 max=1,1,1 material=m rotate=0,1,0,45 translate=0,0,-5\n\
+             cylinder base=0,-1,-5 top=0,1,-5 radius=1 material=m\n\
+             cone base=0,-1,-5 apex=0,1,-5 radius=1 material=m\n\
+             disk center=0,0,-5 normal=0,0,1 radius=1 material=m\n\
+             torus center=0,0,-5 major_radius=2 minor_radius=0.5 material=m\n",
+        )
+        .unwrap();
+        assert_eq!(6, scene.objects().len());
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let expected = [4.0, 5.0 - 2.0f32.sqrt(), 4.0, 4.5, 5.0, 2.5];
+        for (object, expected) in scene.objects().iter().zip(expected) {
+            let d = distance(object, Vec3::default(), forward).unwrap();
+            assert!((d - expected).abs() < 1e-4, "{} {}", d, expected);
+        }
+
+        assert_eq!(
+            (2, 15, Some("max".to_string())),
+            parse_error("material name=m\nbox min=0,0,0 max=1,0,1 material=m")
+        );
+        assert_eq!(
+            (2, 17, Some("apex".to_string())),
+            parse_error("material name=m\ncone base=0,0,0 apex=0,0,0 radius=1 material=m")
+        );
+        assert_eq!(
+            (2, 1, Some("minor_radius".to_string())),
+            parse_error("material name=m\ntorus center=0,0,0 major_radius=1 material=m")
+        );
+    }
+
+    #[test]
+    fn test_parse_planes() {
+        let scene = Scene::parse(
+            "material name=m\n\
+             plane center=0,0,-5 normal=0,0,1\n\
+             plane center=-2,0,0 normal=1,0,0 width=4 height=1 tangent=0,1,0 material=m\n",
+        )
+        .unwrap();
+        assert_eq!(2, scene.objects().len());
+        assert!(!scene.objects()[0].bounds().is_finite());
+        let d = distance(
+            &scene.objects()[0],
+            Vec3::new(100.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        )
+        .unwrap();
+        assert!((d - 5.0).abs() < 1e-5);
+
+        // The width runs along y.
+        let wall = &scene.objects()[1];
+        let left = Vec3::new(-1.0, 0.0, 0.0);
+        assert!(distance(wall, Vec3::new(0.0, 1.5, 0.0), left).is_some());
+        assert!(distance(wall, Vec3::new(0.0, 0.0, 1.0), left).is_none());
+
+        assert_eq!(
+            (1, 33, Some("size".to_string())),
+            parse_error("plane center=0,0,0 normal=0,1,0 size=1 width=1 height=1")
+        );
+        assert_eq!(
+            (1, 1, Some("height".to_string())),
+            parse_error("plane center=0,0,0 normal=0,1,0 width=1")
+        );
+        assert_eq!(
+            (1, 20, Some("normal".to_string())),
+            parse_error("plane center=0,0,0 normal=0,0,0")
+        );
+    }
+
+    #[test]
+    fn test_parse_csg() {
+        let scene = Scene::parse(
+            "material name=m\n\
+             sphere name=a center=-0.5,0,0 radius=1 material=m\n\
+             sphere name=b center=0.5,0,0 radius=1 material=m\n\
+             csg name=lens op=intersection left=a right=b\n\
+             box name=block min=-1,-1,-1 max=1,1,1 material=m\n\
+             csg op=difference left=block right=lens translate=0,0,-5\n",
+        )
+        .unwrap();
+        // Only the last one is placed.
+        assert_eq!(1, scene.objects().len());
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let d = distance(&scene.objects()[0], Vec3::default(), forward).unwrap();
+        assert!((d - 4.0).abs() < 1e-4);
+        // Through the hole the back of the lens is the first surface.
+        let d = distance(&scene.objects()[0], Vec3::new(0.0, 0.0, -5.0), forward).unwrap();
+        assert!((d - 0.75f32.sqrt()).abs() < 1e-4, "{}", d);
+
+        assert_eq!(
+            (2, 5, Some("op".to_string())),
+            parse_error("material name=m\ncsg op=xor left=a right=b")
+        );
+        assert_eq!(
+            (3, 21, Some("right".to_string())),
+            parse_error(
+                "material name=m\nsphere name=a center=0,0,0 radius=1 material=m\ncsg op=union left=a right=b"
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_camera() {
+        let scene = Scene::parse("camera position=0,5,0 look_at=0,0,-10 up=0,1,0").unwrap();
+        assert_eq!(Vec3::new(0.0, 5.0, 0.0), scene.camera().position());
+        assert_eq!(
+            (1, 1, Some("up".to_string())),
+            parse_error("camera position=0,5,0 look_at=0,-5,0")
+        );
+        assert_eq!(0.0, scene.camera().aperture());
+
+        let scene = Scene::parse("camera look_at=0,0,-10 aperture=0.2").unwrap();
+        assert_eq!(0.2, scene.camera().aperture());
+        assert_eq!(10.0, scene.camera().focus_distance());
+        let scene = Scene::parse("camera aperture=0.2 focus_distance=4").unwrap();
+        assert_eq!(4.0, scene.camera().focus_distance());
+        assert_eq!(
+            (1, 8, Some("aperture".to_string())),
+            parse_error("camera aperture=-1")
+        );
+        assert_eq!(
+            (1, 8, Some("focus_distance".to_string())),
+            parse_error("camera focus_distance=0")
+        );
+    }
+
+    #[test]
+    fn test_parse_motion() {
+        let scene = Scene::parse(
+            "camera shutter_open=0 shutter_close=1\n\
+             material name=m\n\
+             sphere center=0,0,-5 radius=1 material=m motion=0:0,0,0;1:2,0,0\n",
+        )
+        .unwrap();
+        assert_eq!(0.0, scene.camera().shutter_open());
+        assert_eq!(1.0, scene.camera().shutter_close());
+        let sphere = &scene.objects()[0];
+        assert_eq!(Vec3::new(3.0, 1.0, -4.0), sphere.bounds().max());
+        let forward = Vec3::new(0.0, 0.0, -1.0);
+        let at = |time| {
+            sphere
+                .intersect(
+                    &Ray::at_time(Vec3::new(2.0, 0.0, 0.0), forward, time),
+                    EPS,
+                    f32::INFINITY,
+                )
+                .map(|hit| hit.t)
+        };
+        assert_eq!(None, at(0.0));
+        assert_eq!(Some(4.0), at(1.0));
+
+        assert_eq!(
+            (2, 42, Some("motion".to_string())),
+            parse_error("material name=m\nsphere center=0,0,-5 radius=1 material=m motion=0:0,0")
+        );
+        assert_eq!(
+            (2, 42, Some("motion".to_string())),
+            parse_error(
+                "material name=m\nsphere center=0,0,-5 radius=1 material=m motion=1:0,0,0;0:1,0,0"
+            )
+        );
+        assert_eq!(
+            (1, 23, Some("shutter_close".to_string())),
+            parse_error("camera shutter_open=1 shutter_close=0.5")
+        );
+    }
+
+    #[test]
+    fn test_parse_animation() {
+        let source = "image fps=10\n\
+             camera position=0:0,0,0;2:0,0,-2 look_at=0,0,-10 shutter_close=0.05\n\
+             material name=m diffuse=0:0,0,0;2:1,1,1 interpolation=smoothstep\n\
+             sphere center=0,0,-5 radius=1 material=m translate=0:0,0,0;1:1,0,0;2:0,0,0 interpolation=catmull_rom\n\
+             light position=0:0,5,0;2:0,1,0\n";
+        let scene = Scene::parse_at(source, 0.5).unwrap();
+        assert_eq!(10.0, scene.fps());
+        assert_eq!(0.5, scene.time());
+        assert_eq!(Vec3::new(0.0, 0.0, -0.5), scene.camera().position());
+        assert_eq!(0.5, scene.camera().shutter_open());
+        assert_eq!(0.55, scene.camera().shutter_close());
+
+        let hit = scene.objects()[0]
+            .intersect(
+                &Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0)),
+                EPS,
+                f32::INFINITY,
+            )
+            .unwrap();
+        // Smoothstep is at 0.15625 a quarter of the way.
+        assert!((hit.material.diffuse_color().x() - 0.15625).abs() < 1e-6);
+        // Catmull-Rom overshoots the straight line towards the middle key.
+        let x = scene.objects()[0].center().x();
+        assert!(x > 0.5 && x < 1.0, "{}", x);
+
+        let mut rng = pixel_rng(0, 0);
+        let samples = scene.lights()[0].samples(Vec3::default(), &mut rng);
+        assert_eq!(4.0, samples[0].distance);
+
+        // Before the first key the values are held.
+        let scene = Scene::parse_at(source, -1.0).unwrap();
+        assert_eq!(Vec3::default(), scene.camera().position());
+
+        assert_eq!(
+            (1, 22, Some("interpolation".to_string())),
+            parse_error("light position=0,0,0 interpolation=linear")
+        );
+        assert_eq!(
+            (1, 32, Some("interpolation".to_string())),
+            parse_error("light position=0:0,0,0;1:1,1,1 interpolation=cubic")
+        );
+        assert_eq!(
+            (1, 8, Some("position".to_string())),
+            parse_error("camera position=0:0,0,0;0:1,1,1")
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_directive() {
+        assert_eq!((1, 1, None), parse_error("cube center=0,0,0"));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+use std::sync::Arc;
+
+/// Alternates between two textures in a checkerboard of `scale x scale`
+/// squares per unit of UV space.
+#[derive(Clone)]
+pub struct Checker {
+    even: Arc<dyn Texture + Sync + Send>,
+    odd: Arc<dyn Texture + Sync + Send>,
+    scale: f32,
+}
+
+impl Checker {
+    pub fn new(
+        even: Arc<dyn Texture + Sync + Send>,
+        odd: Arc<dyn Texture + Sync + Send>,
+        scale: f32,
+    ) -> Self {
+        Self { even, odd, scale }
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+}
+
+impl Texture for Checker {
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3 {
+        let square = (u * self.scale).floor() as i64 + (v * self.scale).floor() as i64;
+        if square.rem_euclid(2) == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::textures::checker::Checker;
+    use crate::textures::solid::SolidColor;
+    use crate::textures::texture::Texture;
+    use crate::vec3::Vec3;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_checker() {
+        let black = Vec3::default();
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        let checker = Checker::new(
+            Arc::new(SolidColor::new(black)),
+            Arc::new(SolidColor::new(white)),
+            4.0,
+        );
+        let p = Vec3::default();
+        assert_eq!(black, checker.value(0.1, 0.1, p));
+        assert_eq!(white, checker.value(0.3, 0.1, p));
+        assert_eq!(white, checker.value(0.1, 0.3, p));
+        assert_eq!(black, checker.value(0.3, 0.3, p));
+        assert_eq!(white, checker.value(-0.1, 0.1, p));
+    }
+}
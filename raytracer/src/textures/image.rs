@@ -0,0 +1,158 @@
+use crate::film::tonemap::srgb_to_linear;
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+use image::codecs::hdr::HdrDecoder;
+use image::{DynamicImage, ImageResult};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/// A texture read from an image file. The image is repeated outside of
+/// `[0, 1]^2` and sampled with bilinear filtering, `v = 1` is its top row.
+#[derive(Clone)]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec3>,
+}
+
+impl ImageTexture {
+    /// `pixels` holds `width * height` colors row by row, starting at the top.
+    pub fn new(width: usize, height: usize, pixels: Vec<Vec3>) -> Self {
+        assert_eq!(width * height, pixels.len());
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Loads an image file. 8 and 16 bit images are taken to be sRGB encoded
+    /// and converted to linear colors, float images, including Radiance
+    /// `.hdr` files, to be linear already.
+    pub fn load<P: AsRef<Path>>(path: P) -> ImageResult<Self> {
+        let path = path.as_ref();
+        let hdr = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+        // `image::open` would convert Radiance files to 8 bit.
+        if hdr {
+            let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+            let (width, height) = (decoder.metadata().width, decoder.metadata().height);
+            let pixels = decoder
+                .read_image_hdr()?
+                .into_iter()
+                .map(|p| Vec3::new(p[0], p[1], p[2]))
+                .collect();
+            return Ok(Self::new(width as usize, height as usize, pixels));
+        }
+
+        let image = image::open(path)?;
+        let linear = matches!(
+            image,
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)
+        );
+        let image = image.into_rgb32f();
+        let decode = |x: f32| if linear { x } else { srgb_to_linear(x) };
+        let pixels = image
+            .pixels()
+            .map(|p| Vec3::new(decode(p[0]), decode(p[1]), decode(p[2])))
+            .collect();
+        Ok(Self::new(
+            image.width() as usize,
+            image.height() as usize,
+            pixels,
+        ))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn pixel(&self, x: i64, y: i64) -> Vec3 {
+        let x = x.rem_euclid(self.width as i64) as usize;
+        let y = y.rem_euclid(self.height as i64) as usize;
+        self.pixels[y * self.width + x]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f32, v: f32, _p: Vec3) -> Vec3 {
+        if self.pixels.is_empty() {
+            return Vec3::default();
+        }
+        // Pixel centers sit at half integer coordinates.
+        let x = u * self.width as f32 - 0.5;
+        let y = (1.0 - v) * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.pixel(x0, y0) * (1.0 - fx) + self.pixel(x0 + 1, y0) * fx;
+        let bottom = self.pixel(x0, y0 + 1) * (1.0 - fx) + self.pixel(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::textures::image::ImageTexture;
+    use crate::textures::texture::Texture;
+    use crate::vec3::Vec3;
+    use image::codecs::hdr::HdrEncoder;
+    use image::Rgb;
+    use std::fs::File;
+
+    #[test]
+    fn test_image_texture() {
+        let red = Vec3::new(1.0, 0.0, 0.0);
+        let blue = Vec3::new(0.0, 0.0, 1.0);
+        // Red on the top row, blue on the bottom row.
+        let texture = ImageTexture::new(2, 2, vec![red, red, blue, blue]);
+        let p = Vec3::default();
+
+        assert_eq!(red, texture.value(0.25, 0.75, p));
+        assert_eq!(blue, texture.value(0.75, 0.25, p));
+        assert_eq!(red * 0.5 + blue * 0.5, texture.value(0.5, 0.5, p));
+        assert_eq!(red, texture.value(1.25, 1.75, p));
+    }
+
+    #[test]
+    fn test_load_image_texture() {
+        let path = std::env::temp_dir().join("raytracer_test_image_texture.png");
+        image::save_buffer(&path, &[255, 0, 0, 0, 188, 0], 2, 1, image::ColorType::Rgb8).unwrap();
+
+        let texture = ImageTexture::load(&path).unwrap();
+        assert_eq!((2, 1), (texture.width(), texture.height()));
+        assert_eq!(
+            Vec3::new(1.0, 0.0, 0.0),
+            texture.value(0.25, 0.5, Vec3::default())
+        );
+        // 188 is the sRGB encoding of about 0.5.
+        let green = texture.value(0.75, 0.5, Vec3::default()).y();
+        assert!((green - 0.5).abs() < 0.01, "{}", green);
+        assert!(ImageTexture::load(path.with_extension("missing")).is_err());
+    }
+
+    #[test]
+    fn test_load_hdr_texture() {
+        let path = std::env::temp_dir().join("raytracer_test_image_texture.hdr");
+        let pixels = [Rgb([4.0, 0.5, 0.0]), Rgb([0.0, 0.0, 1.0])];
+        HdrEncoder::new(File::create(&path).unwrap())
+            .encode(&pixels, 2, 1)
+            .unwrap();
+
+        let texture = ImageTexture::load(&path).unwrap();
+        assert_eq!((2, 1), (texture.width(), texture.height()));
+        let color = texture.value(0.25, 0.5, Vec3::default());
+        assert!(
+            (color - Vec3::new(4.0, 0.5, 0.0)).length() < 0.05,
+            "{:?}",
+            color
+        );
+    }
+}
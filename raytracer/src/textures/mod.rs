@@ -0,0 +1,5 @@
+pub mod checker;
+pub mod image;
+pub mod noise;
+pub mod solid;
+pub mod texture;
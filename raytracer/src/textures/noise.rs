@@ -0,0 +1,150 @@
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise after Ken Perlin: random unit vectors on an integer
+/// lattice, blended with a smooth Hermite curve.
+#[derive(Clone)]
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    permutations: [Vec<usize>; 3],
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| {
+                Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .norm()
+            })
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let permutations = [permutation(), permutation(), permutation()];
+        Self {
+            gradients,
+            permutations,
+        }
+    }
+
+    /// Noise value in about `[-1, 1]`, zero at every lattice point.
+    pub fn noise(&self, p: Vec3) -> f32 {
+        let floor = [p.x().floor(), p.y().floor(), p.z().floor()];
+        let frac = [p.x() - floor[0], p.y() - floor[1], p.z() - floor[2]];
+        let cell = floor.map(|f| f as i64);
+        let smooth = frac.map(|f| f * f * (3.0 - 2.0 * f));
+
+        let mut sum = 0.0;
+        for corner in 0..8 {
+            let offset = [corner & 1, (corner >> 1) & 1, (corner >> 2) & 1];
+            let hash = (0..3).fold(0, |hash, axis| {
+                let index = (cell[axis] + offset[axis] as i64).rem_euclid(POINT_COUNT as i64);
+                hash ^ self.permutations[axis][index as usize]
+            });
+            let weight = Vec3::new(
+                frac[0] - offset[0] as f32,
+                frac[1] - offset[1] as f32,
+                frac[2] - offset[2] as f32,
+            );
+            let blend = (0..3).fold(1.0, |blend, axis| {
+                blend
+                    * if offset[axis] == 1 {
+                        smooth[axis]
+                    } else {
+                        1.0 - smooth[axis]
+                    }
+            });
+            sum += blend * (self.gradients[hash] * weight);
+        }
+        sum
+    }
+
+    /// Sum of `depth` octaves of noise with halving amplitude.
+    pub fn turbulence(&self, p: Vec3, depth: usize) -> f32 {
+        let mut sum = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            sum += weight * self.noise(p);
+            weight *= 0.5;
+            p = p * 2.0;
+        }
+        sum.abs()
+    }
+}
+
+/// Marble-like veins of `color`, `scale` sets the frequency of the pattern
+/// in scene units.
+#[derive(Clone)]
+pub struct NoiseTexture {
+    perlin: Perlin,
+    color: Vec3,
+    scale: f32,
+}
+
+impl NoiseTexture {
+    pub fn new(color: Vec3, scale: f32, seed: u64) -> Self {
+        Self {
+            perlin: Perlin::new(seed),
+            color,
+            scale,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f32, _v: f32, p: Vec3) -> Vec3 {
+        let p = p * self.scale;
+        let phase = p.z() + 10.0 * self.perlin.turbulence(p, 7);
+        self.color * (0.5 * (1.0 + phase.sin()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::textures::noise::{NoiseTexture, Perlin};
+    use crate::textures::texture::Texture;
+    use crate::vec3::Vec3;
+
+    #[test]
+    fn test_perlin() {
+        let perlin = Perlin::new(1);
+        assert_eq!(0.0, perlin.noise(Vec3::new(3.0, -2.0, 7.0)));
+
+        let p = Vec3::new(0.3, 1.7, -2.2);
+        let value = perlin.noise(p);
+        assert!(value.abs() <= 1.0);
+        assert_eq!(value, Perlin::new(1).noise(p));
+        assert_ne!(value, Perlin::new(2).noise(p));
+        // Continuous: nearby points have nearby values.
+        assert!((value - perlin.noise(p + 1e-3)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_noise_texture_scale() {
+        let color = Vec3::new(1.0, 1.0, 1.0);
+        let unit = NoiseTexture::new(color, 1.0, 3);
+        let double = NoiseTexture::new(color, 2.0, 3);
+        // Doubling the scale halves the period of veins and detail alike.
+        for p in [
+            Vec3::new(0.3, 1.7, -2.2),
+            Vec3::new(-4.1, 0.6, 0.9),
+            Vec3::new(2.5, -3.3, 5.7),
+        ] {
+            assert_eq!(unit.value(0.0, 0.0, p * 2.0), double.value(0.0, 0.0, p));
+            assert_ne!(unit.value(0.0, 0.0, p), double.value(0.0, 0.0, p));
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::textures::texture::Texture;
+use crate::vec3::Vec3;
+
+#[derive(Clone)]
+pub struct SolidColor {
+    color: Vec3,
+}
+
+impl SolidColor {
+    pub fn new(color: Vec3) -> Self {
+        Self { color }
+    }
+
+    pub fn color(&self) -> Vec3 {
+        self.color
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f32, _v: f32, _p: Vec3) -> Vec3 {
+        self.color
+    }
+}
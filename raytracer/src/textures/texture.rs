@@ -0,0 +1,8 @@
+use crate::vec3::Vec3;
+
+pub trait Texture {
+    /// Returns the color at the surface coordinates `(u, v)`, which usually
+    /// lie in `[0, 1]`. `p` is the point in the scene, for solid textures
+    /// that do not depend on a parameterisation.
+    fn value(&self, u: f32, v: f32, p: Vec3) -> Vec3;
+}
@@ -7,7 +7,7 @@ pub struct Vec3 {
 
 impl Vec3 {
     #[inline]
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
         Self { x, y, z }
     }
 
@@ -26,6 +26,43 @@ impl Vec3 {
         }
     }
 
+    /// Componentwise product, `*` between two vectors is the dot product.
+    #[inline]
+    pub fn mul_elem(&self, other: Self) -> Self {
+        Self {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+
+    #[inline]
+    pub fn cross(&self, other: Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+
+    #[inline]
+    pub fn min(&self, other: Self) -> Self {
+        Self {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+            z: self.z.min(other.z),
+        }
+    }
+
+    #[inline]
+    pub fn max(&self, other: Self) -> Self {
+        Self {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+            z: self.z.max(other.z),
+        }
+    }
+
     #[inline]
     pub fn x(&self) -> f32 {
         self.x
@@ -49,7 +86,7 @@ impl Default for Vec3 {
     }
 }
 
-use std::ops::{Add, Mul, Neg, Sub};
+use std::ops::{Add, Index, Mul, Neg, Sub};
 
 impl Add<Vec3> for Vec3 {
     type Output = Self;
@@ -131,6 +168,19 @@ impl Neg for Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f32;
+    #[inline]
+    fn index(&self, axis: usize) -> &f32 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Vec3 axis out of range: {}", axis),
+        }
+    }
+}
+
 impl PartialEq for Vec3 {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
@@ -189,4 +239,33 @@ mod tests {
         let vec1 = Vec3::new(1.0, 5.0, 7.0);
         assert_eq!(Vec3::new(-1.0, -5.0, -7.0), -vec1);
     }
+
+    #[test]
+    fn test_vec3_mul_elem() {
+        let vec1 = Vec3::new(1.0, 5.0, 7.0);
+        let vec2 = Vec3::new(-5.0, 10.0, 0.0);
+        assert_eq!(Vec3::new(-5.0, 50.0, 0.0), vec1.mul_elem(vec2));
+    }
+
+    #[test]
+    fn test_vec3_cross() {
+        let vec1 = Vec3::new(1.0, 0.0, 0.0);
+        let vec2 = Vec3::new(0.0, 1.0, 0.0);
+        assert_eq!(Vec3::new(0.0, 0.0, 1.0), vec1.cross(vec2));
+        assert_eq!(Vec3::new(0.0, 0.0, -1.0), vec2.cross(vec1));
+    }
+
+    #[test]
+    fn test_vec3_min_max() {
+        let vec1 = Vec3::new(1.0, 5.0, 7.0);
+        let vec2 = Vec3::new(-5.0, 10.0, 7.0);
+        assert_eq!(Vec3::new(-5.0, 5.0, 7.0), vec1.min(vec2));
+        assert_eq!(Vec3::new(1.0, 10.0, 7.0), vec1.max(vec2));
+    }
+
+    #[test]
+    fn test_vec3_index() {
+        let vec1 = Vec3::new(1.0, 5.0, 7.0);
+        assert_eq!([1.0, 5.0, 7.0], [vec1[0], vec1[1], vec1[2]]);
+    }
 }